@@ -0,0 +1,267 @@
+use crate::math::gcd;
+
+// Points are (x, y) with y growing downwards, the same way the puzzle grids are laid out
+pub type Point = (i64, i64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Winding {
+    Clockwise,
+    CounterClockwise,
+    Degenerate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polygon {
+    vertices: Vec<Point>,
+}
+
+fn cross(origin: Point, a: Point, b: Point) -> i128 {
+    let (ax, ay) = ((a.0 - origin.0) as i128, (a.1 - origin.1) as i128);
+    let (bx, by) = ((b.0 - origin.0) as i128, (b.1 - origin.1) as i128);
+    ax * by - ay * bx
+}
+
+// Positive when the path prev -> current -> next keeps going the same way
+fn dot(prev: Point, current: Point, next: Point) -> i128 {
+    let (ax, ay) = ((current.0 - prev.0) as i128, (current.1 - prev.1) as i128);
+    let (bx, by) = ((next.0 - current.0) as i128, (next.1 - current.1) as i128);
+    ax * bx + ay * by
+}
+
+fn on_segment(a: Point, b: Point, point: Point) -> bool {
+    cross(a, b, point) == 0
+        && point.0 >= a.0.min(b.0)
+        && point.0 <= a.0.max(b.0)
+        && point.1 >= a.1.min(b.1)
+        && point.1 <= a.1.max(b.1)
+}
+
+fn segments_intersect(a: (Point, Point), b: (Point, Point)) -> bool {
+    let d1 = cross(b.0, b.1, a.0).signum();
+    let d2 = cross(b.0, b.1, a.1).signum();
+    let d3 = cross(a.0, a.1, b.0).signum();
+    let d4 = cross(a.0, a.1, b.1).signum();
+    if d1 * d2 < 0 && d3 * d4 < 0 {
+        return true;
+    }
+    on_segment(b.0, b.1, a.0)
+        || on_segment(b.0, b.1, a.1)
+        || on_segment(a.0, a.1, b.0)
+        || on_segment(a.0, a.1, b.1)
+}
+
+impl Polygon {
+    // The polygon is closed implicitly, so repeating the first vertex at the end is optional.
+    // Vertices in the middle of a straight edge are dropped.
+    pub fn from_vertices(vertices: impl IntoIterator<Item = Point>) -> Polygon {
+        let mut points: Vec<Point> = vertices.into_iter().collect();
+        points.dedup();
+        if points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+        let count = points.len();
+        if count < 3 {
+            return Polygon { vertices: points };
+        }
+        let vertices = (0..count)
+            .filter(|index| {
+                let prev = points[(index + count - 1) % count];
+                let next = points[(index + 1) % count];
+                let current = points[*index];
+                cross(prev, current, next) != 0 || dot(prev, current, next) < 0
+            })
+            .map(|index| points[index])
+            .collect();
+        Polygon { vertices }
+    }
+
+    // Walks from `start`, each move being a unit step and how many times to take it
+    pub fn from_moves(start: Point, moves: impl IntoIterator<Item = ([i64; 2], i64)>) -> Polygon {
+        let mut current = start;
+        let mut points = vec![start];
+        for (step, length) in moves {
+            current = (current.0 + step[0] * length, current.1 + step[1] * length);
+            points.push(current);
+        }
+        Polygon::from_vertices(points)
+    }
+
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (Point, Point)> + '_ {
+        let count = self.vertices.len();
+        (0..count).map(move |index| (self.vertices[index], self.vertices[(index + 1) % count]))
+    }
+
+    // Shoelace formula, doubled so that it stays an integer, and summed in i128 like `cross`
+    pub fn doubled_signed_area(&self) -> i128 {
+        self.edges().map(|(a, b)| cross((0, 0), a, b)).sum()
+    }
+
+    pub fn area(&self) -> f64 {
+        self.doubled_signed_area().abs() as f64 / 2.0
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        self.edges()
+            .map(|(a, b)| (((b.0 - a.0).pow(2) + (b.1 - a.1).pow(2)) as f64).sqrt())
+            .sum()
+    }
+
+    pub fn boundary_points(&self) -> i64 {
+        self.edges().map(|(a, b)| gcd(b.0 - a.0, b.1 - a.1)).sum()
+    }
+
+    // Pick's theorem: A = i + b/2 - 1
+    pub fn interior_points(&self) -> i128 {
+        (self.doubled_signed_area().abs() - self.boundary_points() as i128 + 2) / 2
+    }
+
+    pub fn lattice_points(&self) -> i128 {
+        self.interior_points() + self.boundary_points() as i128
+    }
+
+    pub fn winding(&self) -> Winding {
+        match self.doubled_signed_area().signum() {
+            1 => Winding::Clockwise,
+            -1 => Winding::CounterClockwise,
+            _ => Winding::Degenerate,
+        }
+    }
+
+    pub fn locate(&self, point: Point) -> Location {
+        let mut inside = false;
+        for (a, b) in self.edges() {
+            if on_segment(a, b, point) {
+                return Location::Boundary;
+            }
+            // Ray casting towards +x, counting the edges that cross it
+            if (a.1 > point.1) != (b.1 > point.1) {
+                let side = cross(a, b, point);
+                if (b.1 > a.1 && side > 0) || (b.1 < a.1 && side < 0) {
+                    inside = !inside;
+                }
+            }
+        }
+        if inside {
+            Location::Inside
+        } else {
+            Location::Outside
+        }
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        self.locate(point) != Location::Outside
+    }
+
+    pub fn is_self_intersecting(&self) -> bool {
+        let edges: Vec<(Point, Point)> = self.edges().collect();
+        let count = edges.len();
+        if count < 3 {
+            return false;
+        }
+        for first in 0..count {
+            // Neighbouring edges share a vertex, so they only overlap if the path doubles back
+            let (prev, current) = edges[first];
+            let next = edges[(first + 1) % count].1;
+            if cross(prev, current, next) == 0 && dot(prev, current, next) < 0 {
+                return true;
+            }
+            for second in (first + 2)..count {
+                if first == 0 && second == count - 1 {
+                    continue;
+                }
+                if segments_intersect(edges[first], edges[second]) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lagoon() {
+        // Day 18's example dig plan
+        let moves = [
+            ([1, 0], 6),
+            ([0, 1], 5),
+            ([-1, 0], 2),
+            ([0, 1], 2),
+            ([1, 0], 2),
+            ([0, 1], 2),
+            ([-1, 0], 5),
+            ([0, -1], 2),
+            ([-1, 0], 1),
+            ([0, -1], 2),
+            ([1, 0], 2),
+            ([0, -1], 3),
+            ([-1, 0], 2),
+            ([0, -1], 2),
+        ];
+        let lagoon = Polygon::from_moves((0, 0), moves);
+        assert_eq!(lagoon.boundary_points(), 38);
+        assert_eq!(lagoon.lattice_points(), 62);
+        assert_eq!(lagoon.perimeter(), 38.0);
+        assert_eq!(lagoon.winding(), Winding::Clockwise);
+        assert!(!lagoon.is_self_intersecting());
+    }
+
+    #[test]
+    fn triangle() {
+        let triangle = Polygon::from_vertices([(0, 0), (2, 0), (4, 0), (0, 4), (0, 0)]);
+        assert_eq!(triangle.vertices(), &[(0, 0), (4, 0), (0, 4)]);
+        assert_eq!(triangle.doubled_signed_area(), 16);
+        assert_eq!(triangle.area(), 8.0);
+        assert_eq!(triangle.boundary_points(), 12);
+        assert_eq!(triangle.interior_points(), 3);
+        assert_eq!(triangle.locate((1, 1)), Location::Inside);
+        assert_eq!(triangle.locate((2, 2)), Location::Boundary);
+        assert_eq!(triangle.locate((3, 3)), Location::Outside);
+        assert_eq!(triangle.locate((0, 3)), Location::Boundary);
+        assert!(!triangle.contains((-1, 1)));
+
+        let reversed = Polygon::from_vertices([(0, 4), (4, 0), (0, 0)]);
+        assert_eq!(reversed.winding(), Winding::CounterClockwise);
+        assert_eq!(reversed.interior_points(), 3);
+    }
+
+    #[test]
+    fn self_intersection() {
+        let bowtie = Polygon::from_vertices([(0, 0), (2, 2), (2, 0), (0, 2)]);
+        assert!(bowtie.is_self_intersecting());
+        assert_eq!(bowtie.winding(), Winding::Degenerate);
+
+        let spike = Polygon::from_vertices([(0, 0), (4, 0), (2, 0), (2, 2)]);
+        assert!(spike.is_self_intersecting());
+
+        let touching =
+            Polygon::from_vertices([(0, 0), (2, 0), (2, 2), (4, 2), (4, 4), (2, 2), (0, 2)]);
+        assert!(touching.is_self_intersecting());
+
+        let square = Polygon::from_vertices([(0, 0), (3, 0), (3, 3), (0, 3)]);
+        assert!(!square.is_self_intersecting());
+    }
+
+    #[test]
+    fn huge_coordinates() {
+        // Each shoelace term is 2^80, far past what an i64 holds
+        let side = 1 << 40;
+        let square = Polygon::from_vertices([(0, 0), (side, 0), (side, side), (0, side)]);
+        assert_eq!(square.doubled_signed_area(), 1 << 81);
+        assert_eq!(square.lattice_points(), (side as i128 + 1).pow(2));
+    }
+}
@@ -0,0 +1,3 @@
+pub mod geometry;
+pub mod math;
+pub mod parse;
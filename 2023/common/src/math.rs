@@ -0,0 +1,307 @@
+use std::fmt::{Debug, Display};
+use std::ops::{Add, Div, Mul, Rem, Sub};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum MathError {
+    #[error("the result overflows")]
+    Overflow,
+    #[error("the modulus must be positive")]
+    BadModulus,
+    #[error("`{0}` has no inverse modulo `{1}`")]
+    NotInvertible(i128, i128),
+    #[error("the congruences have no common solution")]
+    NoSolution,
+    #[error("`{0}` is negative")]
+    Negative(i128),
+}
+
+pub trait Integer:
+    Copy
+    + Ord
+    + Debug
+    + Display
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+    + TryFrom<i128>
+    + TryInto<i128>
+{
+    const ZERO: Self;
+    const ONE: Self;
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+    fn as_f64(self) -> f64;
+    fn from_f64(value: f64) -> Self;
+}
+
+// Integers whose negatives are representable, so Bézout coefficients fit in them
+pub trait Signed: Integer {}
+
+macro_rules! integer {
+    ($($t:ty),*) => {$(
+        impl Integer for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$t>::checked_add(self, other)
+            }
+            fn checked_sub(self, other: Self) -> Option<Self> {
+                <$t>::checked_sub(self, other)
+            }
+            fn checked_mul(self, other: Self) -> Option<Self> {
+                <$t>::checked_mul(self, other)
+            }
+            fn as_f64(self) -> f64 {
+                self as f64
+            }
+            fn from_f64(value: f64) -> Self {
+                value as $t
+            }
+        }
+    )*};
+}
+
+integer!(i64, u64, i128, u128, usize);
+impl Signed for i64 {}
+impl Signed for i128 {}
+
+fn abs<T: Integer>(value: T) -> Result<T, MathError> {
+    match value < T::ZERO {
+        true => T::ZERO.checked_sub(value).ok_or(MathError::Overflow),
+        false => Ok(value),
+    }
+}
+
+fn to_i128<T: Integer>(value: T) -> Result<i128, MathError> {
+    value.try_into().map_err(|_| MathError::Overflow)
+}
+
+fn from_i128<T: Integer>(value: i128) -> Result<T, MathError> {
+    T::try_from(value).map_err(|_| MathError::Overflow)
+}
+
+fn modulus<T: Integer>(value: T) -> Result<i128, MathError> {
+    match to_i128(value)? {
+        modulus if modulus > 0 => Ok(modulus),
+        _ => Err(MathError::BadModulus),
+    }
+}
+
+// Always non-negative, and only fails when that doesn't fit, like the gcd of i64::MIN and 0
+pub fn checked_gcd<T: Integer>(a: T, b: T) -> Result<T, MathError> {
+    let (mut a, mut b) = (abs(a)?, abs(b)?);
+    while b != T::ZERO {
+        (a, b) = (b, a % b);
+    }
+    Ok(a)
+}
+
+pub fn gcd<T: Integer>(a: T, b: T) -> T {
+    checked_gcd(a, b).expect("the gcd doesn't fit in the type")
+}
+
+pub fn checked_lcm<T: Integer>(a: T, b: T) -> Result<T, MathError> {
+    if a == T::ZERO || b == T::ZERO {
+        return Ok(T::ZERO);
+    }
+    let gcd = checked_gcd(a, b)?;
+    (abs(a)? / gcd)
+        .checked_mul(abs(b)?)
+        .ok_or(MathError::Overflow)
+}
+
+pub fn lcm<T: Integer>(a: T, b: T) -> T {
+    checked_lcm(a, b).expect("the lcm doesn't fit in the type")
+}
+
+pub fn gcd_all<T: Integer>(values: impl IntoIterator<Item = T>) -> Result<T, MathError> {
+    values
+        .into_iter()
+        .try_fold(T::ZERO, |result, value| checked_gcd(result, value))
+}
+
+// The lcm of nothing is 1
+pub fn lcm_all<T: Integer>(values: impl IntoIterator<Item = T>) -> Result<T, MathError> {
+    values
+        .into_iter()
+        .try_fold(T::ONE, |result, value| checked_lcm(result, value))
+}
+
+// (g, x, y) with a * x + b * y = g, where g is the non-negative gcd
+pub fn extended_gcd<T: Signed>(a: T, b: T) -> Result<(T, T, T), MathError> {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (T::ONE, T::ZERO);
+    let (mut old_y, mut y) = (T::ZERO, T::ONE);
+    while r != T::ZERO {
+        let quotient = old_r / r;
+        let next = |old: T, current: T| {
+            quotient
+                .checked_mul(current)
+                .and_then(|product| old.checked_sub(product))
+                .ok_or(MathError::Overflow)
+        };
+        (old_r, r) = (r, next(old_r, r)?);
+        (old_x, x) = (x, next(old_x, x)?);
+        (old_y, y) = (y, next(old_y, y)?);
+    }
+    if old_r < T::ZERO {
+        let negate = |value: T| T::ZERO.checked_sub(value).ok_or(MathError::Overflow);
+        return Ok((negate(old_r)?, negate(old_x)?, negate(old_y)?));
+    }
+    Ok((old_r, old_x, old_y))
+}
+
+// The x in [0, m) with a * x = 1 (mod m)
+pub fn mod_inverse<T: Integer>(a: T, m: T) -> Result<T, MathError> {
+    let m = modulus(m)?;
+    let a = to_i128(a)?.rem_euclid(m);
+    let (gcd, x, _) = extended_gcd(a, m)?;
+    if gcd != 1 {
+        return Err(MathError::NotInvertible(a, m));
+    }
+    from_i128(x.rem_euclid(m))
+}
+
+// Generalised Chinese remainder theorem, the moduli needn't be coprime. Each congruence is a
+// (residue, modulus) pair, and the answer is the residue in [0, lcm) and the lcm of the moduli.
+pub fn crt<T: Integer>(congruences: impl IntoIterator<Item = (T, T)>) -> Result<(T, T), MathError> {
+    let mut result: (i128, i128) = (0, 1);
+    for (residue, m) in congruences {
+        let (a1, m1) = result;
+        let m2 = modulus(m)?;
+        let a2 = to_i128(residue)?.rem_euclid(m2);
+        let (gcd, inverse, _) = extended_gcd(m1, m2)?;
+        if (a2 - a1) % gcd != 0 {
+            return Err(MathError::NoSolution);
+        }
+        let reduced = m2 / gcd;
+        let lcm = (m1 / gcd).checked_mul(m2).ok_or(MathError::Overflow)?;
+        // m1 * inverse = gcd (mod m2), so stepping a1 by m1 this many times lands on a2
+        let steps = ((a2 - a1) / gcd)
+            .rem_euclid(reduced)
+            .checked_mul(inverse.rem_euclid(reduced))
+            .ok_or(MathError::Overflow)?
+            % reduced;
+        let a = m1
+            .checked_mul(steps)
+            .and_then(|offset| offset.checked_add(a1))
+            .ok_or(MathError::Overflow)?;
+        result = (a.rem_euclid(lcm), lcm);
+    }
+    Ok((from_i128(result.0)?, from_i128(result.1)?))
+}
+
+// base^exponent mod m, in [0, m)
+pub fn mod_pow<T: Integer>(base: T, exponent: T, m: T) -> Result<T, MathError> {
+    let m = modulus(m)?;
+    let mut exponent = match to_i128(exponent)? {
+        exponent if exponent < 0 => return Err(MathError::Negative(exponent)),
+        exponent => exponent,
+    };
+    let mut base = to_i128(base)?.rem_euclid(m);
+    let mut result: i128 = 1 % m;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result.checked_mul(base).ok_or(MathError::Overflow)? % m;
+        }
+        base = base.checked_mul(base).ok_or(MathError::Overflow)? % m;
+        exponent >>= 1;
+    }
+    from_i128(result)
+}
+
+// Integer square root, from a floating point estimate corrected with Newton's method
+pub fn isqrt<T: Integer>(n: T) -> Result<T, MathError> {
+    if n < T::ZERO {
+        return Err(MathError::Negative(to_i128(n)?));
+    }
+    let two = T::ONE + T::ONE;
+    if n < two {
+        return Ok(n);
+    }
+    let mut root = T::from_f64(n.as_f64().sqrt()).max(T::ONE);
+    // One step from anywhere lands on or above the root, after that it only descends
+    root = (root + n / root) / two;
+    loop {
+        let next = (root + n / root) / two;
+        if next >= root {
+            break;
+        }
+        root = next;
+    }
+    while root.checked_mul(root).is_none_or(|square| square > n) {
+        root = root - T::ONE;
+    }
+    while (root + T::ONE)
+        .checked_mul(root + T::ONE)
+        .is_some_and(|square| square <= n)
+    {
+        root = root + T::ONE;
+    }
+    Ok(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gcd_and_lcm() {
+        assert_eq!(gcd(12i64, -18), 6);
+        assert_eq!(gcd(0u64, 0), 0);
+        assert_eq!(lcm(4usize, 6), 12);
+        assert_eq!(lcm_all([2i128, 3, 4, 5]), Ok(60));
+        assert_eq!(lcm_all(Vec::<u64>::new()), Ok(1));
+        assert_eq!(gcd_all([12u64, 18, 27]), Ok(3));
+        assert_eq!(checked_gcd(i64::MIN, 0), Err(MathError::Overflow));
+        assert_eq!(lcm_all([u64::MAX, u64::MAX - 1]), Err(MathError::Overflow));
+        let primes = [
+            2i64, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53,
+        ];
+        assert_eq!(lcm_all(primes), Err(MathError::Overflow));
+        assert!(lcm_all(primes.map(i128::from)).is_ok());
+    }
+
+    #[test]
+    fn modular_arithmetic() {
+        assert_eq!(extended_gcd(240i64, 46), Ok((2, -9, 47)));
+        let (gcd, x, y) = extended_gcd(-4i128, 6).unwrap();
+        assert_eq!((gcd, -4 * x + 6 * y), (2, 2));
+        assert_eq!(mod_inverse(3u64, 11), Ok(4));
+        assert_eq!(mod_inverse(-3i64, 11), Ok(7));
+        assert_eq!(mod_inverse(6u64, 9), Err(MathError::NotInvertible(6, 9)));
+        assert_eq!(mod_inverse(1u64, 0), Err(MathError::BadModulus));
+        assert_eq!(mod_pow(2u64, 10, 1000), Ok(24));
+        assert_eq!(mod_pow(-2i64, 3, 5), Ok(2));
+        assert_eq!(mod_pow(7u64, 0, 1), Ok(0));
+        assert_eq!(mod_pow(2i64, -1, 5), Err(MathError::Negative(-1)));
+        let big = (1u64 << 61) - 1;
+        assert_eq!(mod_pow(3u64, big - 1, big), Ok(1));
+    }
+
+    #[test]
+    fn chinese_remainders() {
+        assert_eq!(crt([(2u64, 3), (3, 5), (2, 7)]), Ok((23, 105)));
+        // Moduli sharing a factor
+        assert_eq!(crt([(2i64, 4), (4, 6)]), Ok((10, 12)));
+        assert_eq!(crt([(1i64, 4), (2, 6)]), Err(MathError::NoSolution));
+        assert_eq!(crt([(-1i64, 5)]), Ok((4, 5)));
+        assert_eq!(crt(Vec::<(u64, u64)>::new()), Ok((0, 1)));
+    }
+
+    #[test]
+    fn square_roots() {
+        for n in 0u64..2000 {
+            let root = isqrt(n).unwrap();
+            assert!(root * root <= n && (root + 1) * (root + 1) > n);
+        }
+        assert_eq!(isqrt(u64::MAX), Ok(u32::MAX as u64));
+        assert_eq!(isqrt(u128::MAX), Ok(u64::MAX as u128));
+        assert_eq!(isqrt(i128::MAX), Ok(13043817825332782212));
+        assert_eq!(isqrt(-4i64), Err(MathError::Negative(-4)));
+    }
+}
@@ -0,0 +1,20 @@
+use thiserror::Error;
+
+// A line of the input that couldn't be parsed, with lines counted from 1
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Unable to parse line {line} `{text}`: {reason}")]
+pub struct LineError {
+    pub line: usize,
+    pub text: String,
+    pub reason: &'static str,
+}
+
+impl LineError {
+    pub fn new(line: usize, text: &str, reason: &'static str) -> LineError {
+        LineError {
+            line,
+            text: text.to_string(),
+            reason,
+        }
+    }
+}
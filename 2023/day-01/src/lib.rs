@@ -1,47 +1,297 @@
 use fxhash::FxBuildHasher;
-use std::collections::HashMap;
-
-// Thanks to Tim! https://dev.to/timclicks/two-trie-implementations-in-rust-ones-super-fast-2f3m
+use std::collections::{HashMap, VecDeque};
 
 type FxHashMap<K, V> = HashMap<K, V, FxBuildHasher>;
 
-#[derive(Default, Debug)]
-pub struct TrieNode {
-    pub children: FxHashMap<char, TrieNode>,
-    pub is_key: bool,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Forward,
+    Backward,
+}
+
+// Byte offsets into the text being searched, whichever way it was scanned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    pub start: usize,
+    pub end: usize,
     pub value: char,
 }
 
 #[derive(Default, Debug)]
-pub struct Trie {
-    pub root: TrieNode,
+struct State {
+    next: FxHashMap<char, usize>,
+    fail: usize,
+    // Indexes into `words` of every word that ends here, longest first
+    outputs: Vec<usize>,
 }
 
-impl Trie {
-    pub fn insert(&mut self, word: &str, value: char) {
-        let mut node = &mut self.root;
-        for c in word.chars() {
-            node = node.children.entry(c).or_default();
+// Aho-Corasick automaton over chars. A backward automaton is built from the reversed words and
+// reads the text from its end, so both ways find every match, overlapping ones included,
+// in a single pass.
+#[derive(Debug)]
+pub struct AhoCorasick {
+    states: Vec<State>,
+    words: Vec<(String, char)>,
+    direction: Direction,
+}
+
+impl AhoCorasick {
+    pub fn new<'a>(
+        words: impl IntoIterator<Item = (&'a str, char)>,
+        direction: Direction,
+    ) -> AhoCorasick {
+        let mut automaton = AhoCorasick {
+            states: vec![State::default()],
+            words: Vec::new(),
+            direction,
+        };
+        for (word, value) in words {
+            automaton.insert(word, value);
+        }
+        automaton.link();
+        automaton
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    fn insert(&mut self, word: &str, value: char) {
+        let chars: Vec<char> = match self.direction {
+            Direction::Forward => word.chars().collect(),
+            Direction::Backward => word.chars().rev().collect(),
+        };
+        let mut state = 0;
+        for c in chars {
+            state = match self.states[state].next.get(&c) {
+                Some(next) => *next,
+                None => {
+                    self.states.push(State::default());
+                    let next = self.states.len() - 1;
+                    self.states[state].next.insert(c, next);
+                    next
+                }
+            };
+        }
+        self.states[state].outputs.push(self.words.len());
+        self.words.push((word.to_string(), value));
+    }
+
+    // Failure links, breadth first so every shallower state is linked before it's needed
+    fn link(&mut self) {
+        let mut queue: VecDeque<usize> = self.states[0].next.values().copied().collect();
+        while let Some(state) = queue.pop_front() {
+            let children: Vec<(char, usize)> = self.states[state]
+                .next
+                .iter()
+                .map(|(c, s)| (*c, *s))
+                .collect();
+            for (c, child) in children {
+                let fail = self.step(self.states[state].fail, c);
+                let mut outputs = self.states[fail].outputs.clone();
+                self.states[child].fail = fail;
+                self.states[child].outputs.append(&mut outputs);
+                queue.push_back(child);
+            }
+        }
+    }
+
+    fn step(&self, mut state: usize, c: char) -> usize {
+        loop {
+            if let Some(next) = self.states[state].next.get(&c) {
+                return *next;
+            }
+            if state == 0 {
+                return 0;
+            }
+            state = self.states[state].fail;
+        }
+    }
+
+    // Every match, in the order the scan completes them
+    pub fn find_iter<'a>(&'a self, text: &'a str) -> Matches<'a> {
+        let chars: Box<dyn Iterator<Item = (usize, char)> + 'a> = match self.direction {
+            Direction::Forward => Box::new(text.char_indices()),
+            Direction::Backward => Box::new(text.char_indices().rev()),
+        };
+        Matches {
+            automaton: self,
+            chars,
+            state: 0,
+            pending: Vec::new(),
         }
-        node.is_key = true;
-        node.value = value;
     }
-    pub fn contains(&mut self, word: &str) -> (bool, char) {
-        let mut node = &self.root;
 
-        for c in word.chars() {
-            if node.is_key {
-                return (node.is_key, node.value);
+    // The match nearest the end the scan starts from: the leftmost start going forward, or the
+    // rightmost end going backward. The scan stops once no longer word could beat it.
+    pub fn first_match(&self, text: &str) -> Option<Match> {
+        let longest = self.words.iter().map(|(word, _)| word.len()).max()?;
+        let mut best: Option<Match> = None;
+        for found in self.find_iter(text) {
+            if let Some(best) = best {
+                let (beaten, done) = match self.direction {
+                    Direction::Forward => {
+                        (found.start < best.start, found.end >= best.start + longest)
+                    }
+                    Direction::Backward => {
+                        (found.end > best.end, found.start + longest <= best.end)
+                    }
+                };
+                if done {
+                    break;
+                }
+                if !beaten {
+                    continue;
+                }
+            }
+            best = Some(found);
+        }
+        best
+    }
+}
+
+pub struct Matches<'a> {
+    automaton: &'a AhoCorasick,
+    chars: Box<dyn Iterator<Item = (usize, char)> + 'a>,
+    state: usize,
+    pending: Vec<Match>,
+}
+
+impl Iterator for Matches<'_> {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Match> {
+        while self.pending.is_empty() {
+            let (index, c) = self.chars.next()?;
+            self.state = self.automaton.step(self.state, c);
+            // Outputs are stored longest first, pending is popped from the back
+            for word in self.automaton.states[self.state].outputs.iter().rev() {
+                let (text, value) = &self.automaton.words[*word];
+                let (start, end) = match self.automaton.direction {
+                    Direction::Forward => (index + c.len_utf8() - text.len(), index + c.len_utf8()),
+                    Direction::Backward => (index, index + text.len()),
+                };
+                self.pending.push(Match {
+                    start,
+                    end,
+                    value: *value,
+                });
             }
-            if c.is_numeric() {
-                return (true, c);
+        }
+        self.pending.pop()
+    }
+}
+
+// One `<word> <digit>` pair per line
+pub const ENGLISH: &str = "
+one 1
+two 2
+three 3
+four 4
+five 5
+six 6
+seven 7
+eight 8
+nine 9
+";
+
+pub const SPANISH: &str = "
+uno 1
+dos 2
+tres 3
+cuatro 4
+cinco 5
+seis 6
+siete 7
+ocho 8
+nueve 9
+";
+
+pub const GERMAN: &str = "
+eins 1
+zwei 2
+drei 3
+vier 4
+fünf 5
+sechs 6
+sieben 7
+acht 8
+neun 9
+";
+
+// Words that spell out digits. The first digit of a line is found reading forward and the last
+// reading backward, so neither query looks further into the line than it has to.
+#[derive(Debug)]
+pub struct DigitLexicon {
+    words: Vec<(String, char)>,
+    first: AhoCorasick,
+    last: AhoCorasick,
+}
+
+impl DigitLexicon {
+    pub fn new(words: Vec<(String, char)>) -> DigitLexicon {
+        let pairs = || words.iter().map(|(word, value)| (word.as_str(), *value));
+        let first = AhoCorasick::new(pairs(), Direction::Forward);
+        let last = AhoCorasick::new(pairs(), Direction::Backward);
+        DigitLexicon { words, first, last }
+    }
+
+    pub fn english() -> DigitLexicon {
+        DigitLexicon::from_config(ENGLISH).expect("the built-in English lexicon is valid")
+    }
+
+    pub fn spanish() -> DigitLexicon {
+        DigitLexicon::from_config(SPANISH).expect("the built-in Spanish lexicon is valid")
+    }
+
+    pub fn german() -> DigitLexicon {
+        DigitLexicon::from_config(GERMAN).expect("the built-in German lexicon is valid")
+    }
+
+    pub fn from_config(config: &str) -> Result<DigitLexicon, String> {
+        let mut words = Vec::new();
+        for line in config.lines().filter(|line| !line.trim().is_empty()) {
+            let mut fields = line.split_whitespace();
+            let (word, value) = match (fields.next(), fields.next(), fields.next()) {
+                (Some(word), Some(value), None) => (word, value),
+                _ => return Err(line.to_string()),
+            };
+            let mut value_chars = value.chars();
+            let value = match (value_chars.next(), value_chars.next()) {
+                (Some(value), None) if value.is_ascii_digit() => value,
+                _ => return Err(line.to_string()),
+            };
+            if words.iter().any(|(known, _)| known == word) {
+                return Err(line.to_string());
             }
-            match node.children.get(&c) {
-                Some(child_node) => node = child_node,
-                None => return (false, node.value),
+            words.push((word.to_string(), value));
+        }
+        Ok(DigitLexicon::new(words))
+    }
+
+    // Also matches the digits themselves, as the calibration values need
+    pub fn with_digits(mut self) -> DigitLexicon {
+        for digit in '0'..='9' {
+            if !self
+                .words
+                .iter()
+                .any(|(word, _)| *word == digit.to_string())
+            {
+                self.words.push((digit.to_string(), digit));
             }
         }
+        DigitLexicon::new(self.words)
+    }
+
+    pub fn words(&self) -> &[(String, char)] {
+        &self.words
+    }
+
+    pub fn first_match(&self, text: &str) -> Option<Match> {
+        self.first.first_match(text)
+    }
 
-        (node.is_key, node.value)
+    pub fn last_match(&self, text: &str) -> Option<Match> {
+        self.last.first_match(text)
     }
 }
@@ -1,95 +1,117 @@
 use anyhow::Result;
-use day_01::Trie;
-use std::fmt::Error;
+use common::parse::LineError;
+use day_01::DigitLexicon;
+use thiserror::Error;
 
-struct Word {
-    n: &'static str,
-    v: char,
+#[derive(Debug, Error)]
+pub enum AoCError {
+    #[error("Unable to parse the input `{0}`")]
+    ParsingError(String),
+    #[error(transparent)]
+    LineError(#[from] LineError),
+    #[error("An unknown error has occurred (super duper helpful error)")]
+    Unknown,
 }
 
-const NUMBERS: [Word; 9] = [
-    Word { n: "one", v: '1' },
-    Word { n: "two", v: '2' },
-    Word { n: "three", v: '3' },
-    Word { n: "four", v: '4' },
-    Word { n: "five", v: '5' },
-    Word { n: "six", v: '6' },
-    Word { n: "seven", v: '7' },
-    Word { n: "eight", v: '8' },
-    Word { n: "nine", v: '9' },
-];
-
 fn main() {
     let input: &str = include_str!("./input.txt");
-    let result: i32 = input
-        .split('\n')
-        .map(|line| get_digits_part_1(line).unwrap())
-        .map(|line| line.parse::<i32>().unwrap())
-        .sum();
-    println!("Part 1 result: {}", result);
+    match process_part_1(input) {
+        Ok(result) => println!("Part 1 result: {}", result),
+        Err(e) => println!("Error: {}", e),
+    }
+    // Usage: day-01 [--lexicon <english|spanish|german|file>]
+    let mut lexicon_name = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--lexicon" => lexicon_name = args.next(),
+            _ => println!("Ignoring unknown argument `{}`", arg),
+        }
+    }
+    let lexicon = match load_lexicon(lexicon_name) {
+        Ok(lexicon) => lexicon,
+        Err(e) => {
+            println!("Error: {}", e);
+            return;
+        }
+    };
+    match process_part_2(input, &lexicon) {
+        Ok(result) => println!("Part 2 result: {}", result),
+        Err(e) => println!("Error: {}", e),
+    }
+}
+
+// Sums the calibration values, blank lines are skipped
+fn sum_calibration(
+    input: &str,
+    mut get_digits: impl FnMut(&str) -> Option<String>,
+) -> Result<i32, AoCError> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(num, line)| {
+            let error = |reason| AoCError::LineError(LineError::new(num + 1, line, reason));
+            let digits = get_digits(line).ok_or_else(|| error("no calibration digits"))?;
+            digits
+                .parse::<i32>()
+                .map_err(|_| error("calibration value isn't a number"))
+        })
+        .sum()
+}
+
+fn process_part_1(input: &str) -> Result<i32, AoCError> {
+    sum_calibration(input, get_digits_part_1)
+}
 
-    // Part 2
-    let mut trie = setup_trie();
-    let result_2: i32 = input
-        .split('\n')
-        .map(|line| get_digits_part_2(line, &mut trie).unwrap())
-        .map(|line| line.parse::<i32>().unwrap())
-        .sum();
-    println!("Part 2 result: {}", result_2);
+fn process_part_2(input: &str, lexicon: &DigitLexicon) -> Result<i32, AoCError> {
+    sum_calibration(input, |line| get_digits_part_2(line, lexicon))
 }
 
-fn get_digits_part_1(input: &str) -> Result<String, Error> {
+fn load_lexicon(name: Option<String>) -> Result<DigitLexicon, AoCError> {
+    let lexicon = match name.as_deref() {
+        None | Some("english") => DigitLexicon::english(),
+        Some("spanish") => DigitLexicon::spanish(),
+        Some("german") => DigitLexicon::german(),
+        Some(path) => {
+            let config = std::fs::read_to_string(path)
+                .map_err(|e| AoCError::ParsingError(format!("{path}: {e}")))?;
+            DigitLexicon::from_config(&config).map_err(AoCError::ParsingError)?
+        }
+    };
+    Ok(lexicon.with_digits())
+}
+
+fn get_digits_part_1(input: &str) -> Option<String> {
     let mut ans: String = Default::default();
     for c in input.chars() {
-        if c.is_numeric() {
+        if c.is_ascii_digit() {
             ans.push(c);
             break;
         }
     }
     for c in input.chars().rev() {
-        if c.is_numeric() {
+        if c.is_ascii_digit() {
             ans.push(c);
             break;
         }
     }
     if ans.len() != 2 {
-        return Err(Error);
-    }
-    Ok(ans)
-}
-
-fn setup_trie() -> Trie {
-    let mut trie = Trie::default();
-    for num in NUMBERS.iter() {
-        trie.insert(num.n, num.v);
+        return None;
     }
-    trie
+    Some(ans)
 }
 
-fn get_digits_part_2(input: &str, trie: &mut Trie) -> Result<String, Error> {
-    let mut ans: String = Default::default();
-    for (index, _c) in input.char_indices() {
-        let s = &input[index..];
-        let (contains, c) = trie.contains(s);
-        if contains {
-            if ans.is_empty() {
-                ans.push(c);
-                ans.push(c);
-            } else {
-                ans.pop();
-                ans.push(c);
-            }
-        }
-    }
-    if ans.len() != 2 {
-        return Err(Error);
-    }
-    Ok(ans)
+fn get_digits_part_2(input: &str, lexicon: &DigitLexicon) -> Option<String> {
+    let first = lexicon.first_match(input)?;
+    let last = lexicon.last_match(input)?;
+    Some([first.value, last.value].iter().collect())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use day_01::{AhoCorasick, Direction};
     #[test]
     fn test_get_digits_pt_1() -> Result<()> {
         assert_eq!(get_digits_part_1("1abc2").unwrap(), "12".to_string());
@@ -100,35 +122,120 @@ mod tests {
     }
     #[test]
     fn test_get_digits_pt_2() -> Result<()> {
-        let mut trie = setup_trie();
+        let lexicon = DigitLexicon::english().with_digits();
         assert_eq!(
-            get_digits_part_2("two1nine", &mut trie).unwrap(),
+            get_digits_part_2("two1nine", &lexicon).unwrap(),
             "29".to_string()
         );
         assert_eq!(
-            get_digits_part_2("eightwothree", &mut trie).unwrap(),
+            get_digits_part_2("eightwothree", &lexicon).unwrap(),
             "83".to_string()
         );
         assert_eq!(
-            get_digits_part_2("abcone2threexyz", &mut trie).unwrap(),
+            get_digits_part_2("abcone2threexyz", &lexicon).unwrap(),
             "13".to_string()
         );
         assert_eq!(
-            get_digits_part_2("xtwone3four", &mut trie).unwrap(),
+            get_digits_part_2("xtwone3four", &lexicon).unwrap(),
             "24".to_string()
         );
         assert_eq!(
-            get_digits_part_2("4nineeightseven2", &mut trie).unwrap(),
+            get_digits_part_2("4nineeightseven2", &lexicon).unwrap(),
             "42".to_string()
         );
         assert_eq!(
-            get_digits_part_2("zoneight234", &mut trie).unwrap(),
+            get_digits_part_2("zoneight234", &lexicon).unwrap(),
             "14".to_string()
         );
         assert_eq!(
-            get_digits_part_2("7pqrstsixteen", &mut trie).unwrap(),
+            get_digits_part_2("7pqrstsixteen", &lexicon).unwrap(),
             "76".to_string()
         );
         Ok(())
     }
+    #[test]
+    fn malformed_input() -> Result<()> {
+        assert_eq!(process_part_1("1abc2\n\npqr3stu8vwx\n")?, 50);
+        let error = process_part_1("1abc2\nabc").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unable to parse line 2 `abc`: no calibration digits"
+        );
+        assert!(process_part_2("two1nine\nxyz", &DigitLexicon::english()).is_err());
+        Ok(())
+    }
+    #[test]
+    fn overlapping_words() {
+        let lexicon = DigitLexicon::english().with_digits();
+        let words = || lexicon.words().iter().map(|(word, v)| (word.as_str(), *v));
+        let forward = AhoCorasick::new(words(), Direction::Forward);
+        let backward = AhoCorasick::new(words(), Direction::Backward);
+        let found: Vec<(usize, usize, char)> = forward
+            .find_iter("zoneight2")
+            .map(|m| (m.start, m.end, m.value))
+            .collect();
+        assert_eq!(found, vec![(1, 4, '1'), (3, 8, '8'), (8, 9, '2')]);
+        let backward: Vec<char> = backward.find_iter("zoneight2").map(|m| m.value).collect();
+        assert_eq!(backward, vec!['2', '8', '1']);
+        assert_eq!(get_digits_part_2("oneight", &lexicon).unwrap(), "18");
+        assert_eq!(get_digits_part_2("sevenine", &lexicon).unwrap(), "79");
+
+        // Words nested inside each other are all reported, the longest first
+        let nested = AhoCorasick::new(
+            [("ab", 'a'), ("abcd", 'b'), ("bc", 'c')],
+            Direction::Forward,
+        );
+        let found: Vec<char> = nested.find_iter("xabcd").map(|m| m.value).collect();
+        assert_eq!(found, vec!['a', 'c', 'b']);
+        assert_eq!(nested.first_match("xbcabcd").unwrap().value, 'c');
+    }
+    #[test]
+    fn unicode_words() {
+        let words = [
+            ("uno", '1'),
+            ("dós", '2'),
+            ("drei", '3'),
+            ("fünf", '5'),
+            ("五", '5'),
+        ];
+        let forward = AhoCorasick::new(words, Direction::Forward);
+        let backward = AhoCorasick::new(words, Direction::Backward);
+        let text = "xfünfdósüdrei五z";
+        let found: Vec<char> = forward.find_iter(text).map(|m| m.value).collect();
+        assert_eq!(found, vec!['5', '2', '3', '5']);
+        let first = forward.first_match(text).unwrap();
+        assert_eq!(&text[first.start..first.end], "fünf");
+        let last = backward.first_match(text).unwrap();
+        assert_eq!(&text[last.start..last.end], "五");
+        assert_eq!(backward.direction(), Direction::Backward);
+        assert!(forward.first_match("nada").is_none());
+    }
+    #[test]
+    fn lexicons() -> Result<()> {
+        let spanish = DigitLexicon::spanish().with_digits();
+        assert_eq!(
+            get_digits_part_2("xdosiete8ochonueve", &spanish).unwrap(),
+            "29"
+        );
+        assert_eq!(get_digits_part_2("cuatrocinco", &spanish).unwrap(), "45");
+        let german = DigitLexicon::german();
+        assert_eq!(
+            get_digits_part_2("fünfzigundsieben4", &german).unwrap(),
+            "57"
+        );
+        assert_eq!(
+            process_part_2("einsacht\nzweiundzwanzig\n", &german)?,
+            18 + 22
+        );
+
+        let custom = DigitLexicon::from_config("un 1\ndeux 2\n\ntrois 3\n").unwrap();
+        assert_eq!(custom.words().len(), 3);
+        let last = custom.last_match("undeuxtroisun").unwrap();
+        assert_eq!((last.start, last.end, last.value), (11, 13, '1'));
+        assert_eq!(custom.first_match("troisdeux").unwrap().value, '3');
+        assert!(DigitLexicon::from_config("un 1 2").is_err());
+        assert!(DigitLexicon::from_config("un one").is_err());
+        assert!(DigitLexicon::from_config("un 1\nun 2").is_err());
+        Ok(())
+    }
 }
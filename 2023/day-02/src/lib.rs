@@ -0,0 +1,133 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashMap;
+
+pub type Colour = String;
+
+// How many cubes of each colour; a colour that isn't listed has none
+pub type Bag = HashMap<Colour, u32>;
+
+pub const DEFAULT_BAG: &str = "12 red, 13 green, 14 blue";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    pub id: u32,
+    pub rounds: Vec<HashMap<Colour, u32>>,
+}
+
+lazy_static! {
+    static ref CUBE_MATCH: Regex = Regex::new(r"^\s*(\d+) ([a-z]+)\s*$").unwrap();
+}
+
+fn parse_cube(cube: &str) -> Result<(u32, &str), &'static str> {
+    let capture = CUBE_MATCH
+        .captures(cube)
+        .ok_or("expected `<count> <colour>`")?;
+    let count = capture[1]
+        .parse::<u32>()
+        .map_err(|_| "cube count is too large")?;
+    Ok((count, capture.get(2).map_or("", |colour| colour.as_str())))
+}
+
+// `<count> <colour>` pairs separated by `separators`, counting a repeated colour twice
+fn parse_cubes(input: &str, separators: &[char]) -> Result<HashMap<Colour, u32>, &'static str> {
+    let mut cubes = HashMap::new();
+    for cube in input
+        .split(separators)
+        .filter(|cube| !cube.trim().is_empty())
+    {
+        let (count, colour) = parse_cube(cube)?;
+        let total: &mut u32 = cubes.entry(colour.to_string()).or_default();
+        *total = total.checked_add(count).ok_or("cube count is too large")?;
+    }
+    Ok(cubes)
+}
+
+// Bag limits use the same `<count> <colour>` pairs as the rounds, separated by commas or
+// newlines so they can be read from a config file
+pub fn parse_bag(input: &str) -> Result<Bag, &'static str> {
+    parse_cubes(input, &[',', '\n'])
+}
+
+// The smallest bag that holds at least as many cubes of every colour as each of `bags`
+pub fn union(bags: impl IntoIterator<Item = Bag>) -> Bag {
+    let mut result = Bag::new();
+    for bag in bags {
+        for (colour, count) in bag {
+            let max = result.entry(colour).or_default();
+            *max = (*max).max(count);
+        }
+    }
+    result
+}
+
+pub fn fits(needed: &Bag, bag: &Bag) -> bool {
+    needed
+        .iter()
+        .all(|(colour, count)| *count <= bag.get(colour).copied().unwrap_or(0))
+}
+
+// Colours sorted by name so the output doesn't depend on the hash order
+pub fn format_bag(bag: &Bag) -> String {
+    let mut cubes: Vec<(&Colour, &u32)> = bag.iter().collect();
+    cubes.sort();
+    cubes
+        .iter()
+        .map(|(colour, count)| format!("{} {}", count, colour))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// The games are possible with exactly the bags that hold at least this many cubes of each colour
+pub fn feasible_bags<'a>(games: impl IntoIterator<Item = &'a Game>) -> Bag {
+    union(games.into_iter().map(Game::minimal_bag))
+}
+
+impl Game {
+    // Parses `Game <id>: <round>; <round>; ...`
+    pub fn parse(input: &str) -> Result<Game, &'static str> {
+        let (game, rounds) = input
+            .split_once(':')
+            .ok_or("missing `:` after the game id")?;
+        let id = game
+            .strip_prefix("Game ")
+            .and_then(|id| id.trim().parse::<u32>().ok())
+            .ok_or("expected `Game <id>`")?;
+        let rounds = rounds
+            .split(';')
+            .map(|round| match round.trim().is_empty() {
+                true => Err("empty round"),
+                false => parse_cubes(round, &[',']),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Game { id, rounds })
+    }
+
+    pub fn minimal_bag(&self) -> Bag {
+        union(self.rounds.iter().cloned())
+    }
+
+    pub fn is_possible(&self, bag: &Bag) -> bool {
+        fits(&self.minimal_bag(), bag)
+    }
+
+    // Product of the counts in the game's own minimal bag, whatever colours it draws
+    pub fn power(&self) -> u64 {
+        self.minimal_bag()
+            .values()
+            .map(|count| *count as u64)
+            .product()
+    }
+
+    // The colour with the fewest cubes to spare in `bag`, and how many that is.
+    // Negative spare means the game is impossible because of that colour.
+    pub fn most_restrictive(&self, bag: &Bag) -> Option<(Colour, i64)> {
+        self.minimal_bag()
+            .into_iter()
+            .map(|(colour, needed)| {
+                let spare = bag.get(&colour).copied().unwrap_or(0) as i64 - needed as i64;
+                (colour, spare)
+            })
+            .min_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)))
+    }
+}
@@ -1,69 +1,142 @@
 use anyhow::Result;
-use regex::Regex;
-use std::fmt::Error;
+use common::parse::LineError;
+use day_02::{feasible_bags, format_bag, parse_bag, Bag, Game, DEFAULT_BAG};
+use thiserror::Error;
 
-const MAX_RED: i32 = 12;
-const MAX_GREEN: i32 = 13;
-const MAX_BLUE: i32 = 14;
+#[derive(Debug, Error)]
+pub enum AoCError {
+    #[error("Unable to parse the input `{0}`")]
+    ParsingError(String),
+    #[error(transparent)]
+    LineError(#[from] LineError),
+    #[error("Unable to load the bag `{0}`")]
+    BagError(String),
+    #[error("An unknown error has occurred (super duper helpful error)")]
+    Unknown,
+}
 
 fn main() {
     let input: &str = include_str!("./input.txt");
-    let result_1: i32 = input.split('\n').map(|line| part_1(line).unwrap()).sum();
-    println!("Part 1 result: {}", result_1);
-    let result_2: i32 = input.split('\n').map(|line| part_2(line).unwrap()).sum();
-    println!("Part 2 result: {}", result_2);
+    // Usage: day-02 [--bag <count colour, ...>] [--bag-file <file>] [--games <id,id,...>] [--report]
+    let mut bag_spec = DEFAULT_BAG.to_string();
+    let mut queried_games: Option<String> = None;
+    let mut report = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--bag" => bag_spec = args.next().unwrap_or_default(),
+            "--bag-file" => match args.next().map(std::fs::read_to_string) {
+                Some(Ok(contents)) => bag_spec = contents,
+                _ => println!("Unable to read the bag file, using `{}`", bag_spec),
+            },
+            "--games" => queried_games = args.next(),
+            "--report" => report = true,
+            _ => println!("Ignoring unknown argument `{}`", arg),
+        }
+    }
+    let bag = match parse_bag(&bag_spec) {
+        Ok(bag) => bag,
+        Err(reason) => {
+            println!(
+                "Error: {}",
+                AoCError::BagError(format!("{}: {}", bag_spec, reason))
+            );
+            return;
+        }
+    };
+    match process_part_1(input, &bag) {
+        Ok(result) => println!("Part 1 result: {}", result),
+        Err(e) => println!("Error: {}", e),
+    }
+    match process_part_2(input) {
+        Ok(result) => println!("Part 2 result: {}", result),
+        Err(e) => println!("Error: {}", e),
+    }
+    if report {
+        if let Err(e) = print_report(input, &bag) {
+            println!("Error: {}", e);
+        }
+    }
+    if let Some(ids) = queried_games {
+        match query_feasible_bags(input, &ids) {
+            Ok(bag) => println!(
+                "Games {} are possible with any bag holding at least {}",
+                ids,
+                format_bag(&bag)
+            ),
+            Err(e) => println!("Error: {}", e),
+        }
+    }
 }
 
-fn part_1(input: &str) -> Result<i32, Error> {
-    let cube_match = Regex::new(r"(\d+) (red|green|blue)").unwrap();
-    let line: Vec<&str> = input.split(':').collect();
-    let game_id = line[0][5..].parse::<i32>().unwrap();
-    let games = line[1].split(';');
-    for game in games {
-        let cubes = game.split(',');
-        for cube in cubes {
-            let capture = cube_match.captures(cube).unwrap();
-            let count = capture[1].parse::<i32>().unwrap();
-            let cube_max = match &capture[2] {
-                "red" => MAX_RED,
-                "green" => MAX_GREEN,
-                "blue" => MAX_BLUE,
-                _ => return Err(Error),
-            };
-            if count > cube_max {
-                return Ok(0);
-            }
-        }
+fn parse_games(input: &str) -> Result<Vec<Game>, AoCError> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(num, line)| {
+            Game::parse(line)
+                .map_err(|reason| AoCError::LineError(LineError::new(num + 1, line, reason)))
+        })
+        .collect()
+}
+
+fn process_part_1(input: &str, bag: &Bag) -> Result<u32, AoCError> {
+    Ok(parse_games(input)?
+        .iter()
+        .filter(|game| game.is_possible(bag))
+        .map(|game| game.id)
+        .sum())
+}
+
+fn process_part_2(input: &str) -> Result<u64, AoCError> {
+    Ok(parse_games(input)?.iter().map(Game::power).sum())
+}
+
+fn print_report(input: &str, bag: &Bag) -> Result<(), AoCError> {
+    for game in parse_games(input)? {
+        let restrictive = match game.most_restrictive(bag) {
+            Some((colour, spare)) => format!("{} ({} to spare)", colour, spare),
+            None => "none".to_string(),
+        };
+        println!(
+            "Game {}: minimal bag {}, most restrictive colour {}",
+            game.id,
+            format_bag(&game.minimal_bag()),
+            restrictive
+        );
     }
-    Ok(game_id)
+    Ok(())
 }
 
-fn part_2(input: &str) -> Result<i32, Error> {
-    let cube_match = Regex::new(r"(\d+) (red|green|blue)").unwrap();
-    let line: Vec<&str> = input.split(':').collect();
-    let games = line[1].split(';');
-    let mut red = 0;
-    let mut green = 0;
-    let mut blue = 0;
-    for game in games {
-        let cubes = game.split(',');
-        for cube in cubes {
-            let capture = cube_match.captures(cube).unwrap();
-            let count = capture[1].parse::<i32>().unwrap();
-            match &capture[2] {
-                "red" => red = std::cmp::max(red, count),
-                "green" => green = std::cmp::max(green, count),
-                "blue" => blue = std::cmp::max(blue, count),
-                _ => return Err(Error),
-            };
-        }
+// The bags under which every listed game is possible, given by the smallest of them
+fn query_feasible_bags(input: &str, ids: &str) -> Result<Bag, AoCError> {
+    let games = parse_games(input)?;
+    let mut selected = Vec::new();
+    for id in ids.split(',').map(str::trim) {
+        let game = id
+            .parse::<u32>()
+            .ok()
+            .and_then(|id| games.iter().find(|game| game.id == id))
+            .ok_or_else(|| AoCError::ParsingError(format!("no game with id {}", id)))?;
+        selected.push(game);
     }
-    Ok(red * green * blue)
+    Ok(feasible_bags(selected))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
+
+    fn possible(input: &str) -> Result<u32, AoCError> {
+        process_part_1(input, &parse_bag(DEFAULT_BAG).unwrap())
+    }
+
+    fn power(input: &str) -> Result<u64, AoCError> {
+        process_part_2(input)
+    }
+
     #[test]
     fn test_1() -> Result<()> {
         let input_1 = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green";
@@ -71,11 +144,11 @@ mod tests {
         let input_3 = "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red";
         let input_4 = "Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red";
         let input_5 = "Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
-        assert_eq!(part_1(input_1).unwrap(), 1);
-        assert_eq!(part_1(input_2).unwrap(), 2);
-        assert_eq!(part_1(input_3).unwrap(), 0);
-        assert_eq!(part_1(input_4).unwrap(), 0);
-        assert_eq!(part_1(input_5).unwrap(), 5);
+        assert_eq!(possible(input_1).unwrap(), 1);
+        assert_eq!(possible(input_2).unwrap(), 2);
+        assert_eq!(possible(input_3).unwrap(), 0);
+        assert_eq!(possible(input_4).unwrap(), 0);
+        assert_eq!(possible(input_5).unwrap(), 5);
         Ok(())
     }
     #[test]
@@ -85,11 +158,68 @@ mod tests {
         let input_3 = "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red";
         let input_4 = "Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red";
         let input_5 = "Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
-        assert_eq!(part_2(input_1).unwrap(), 48);
-        assert_eq!(part_2(input_2).unwrap(), 12);
-        assert_eq!(part_2(input_3).unwrap(), 1560);
-        assert_eq!(part_2(input_4).unwrap(), 630);
-        assert_eq!(part_2(input_5).unwrap(), 36);
+        assert_eq!(power(input_1).unwrap(), 48);
+        assert_eq!(power(input_2).unwrap(), 12);
+        assert_eq!(power(input_3).unwrap(), 1560);
+        assert_eq!(power(input_4).unwrap(), 630);
+        assert_eq!(power(input_5).unwrap(), 36);
+        Ok(())
+    }
+    #[test]
+    fn malformed_input() -> Result<()> {
+        let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4";
+        let error = power(input).unwrap_err();
+        assert!(matches!(
+            error,
+            AoCError::LineError(LineError { line: 3, .. })
+        ));
+        assert!(error.to_string().contains("5 blue, 4`"));
+        assert!(power("Game 1 3 blue").is_err());
+        assert!(power("Gaem 1: 3 blue").is_err());
+        assert!(power("Game 1: 3 blue;; 2 red").is_err());
+        assert_eq!(power("Game 1: 3 blue, 4 red; 2 green\n")?, 24);
+        Ok(())
+    }
+    #[test]
+    fn configurable_bags() -> Result<()> {
+        let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 2 purple, 1 red";
+        // Colours outside the bag can't be drawn at all
+        assert_eq!(possible(input)?, 3);
+        let bag = parse_bag("4 red\n6 blue, 3 green\n2 purple").unwrap();
+        assert_eq!(process_part_1(input, &bag)?, 6);
+        // Power comes from each game's own minimal bag, so the bag doesn't change it
+        assert_eq!(process_part_2(input)?, 48 + 12 + 2);
+        assert!(parse_bag("4 red, lots of blue").is_err());
+
+        let games = parse_games(input)?;
+        assert_eq!(
+            games[2].rounds,
+            vec![HashMap::from([
+                ("purple".to_string(), 2),
+                ("red".to_string(), 1)
+            ])]
+        );
+        assert_eq!(
+            games[0].most_restrictive(&parse_bag(DEFAULT_BAG).unwrap()),
+            Some(("blue".to_string(), 8))
+        );
+        assert_eq!(
+            games[2].most_restrictive(&parse_bag(DEFAULT_BAG).unwrap()),
+            Some(("purple".to_string(), -2))
+        );
+        assert_eq!(
+            format_bag(&feasible_bags(&games[..2])),
+            "6 blue, 3 green, 4 red"
+        );
+        assert_eq!(
+            format_bag(&query_feasible_bags(input, "2, 3")?),
+            "4 blue, 3 green, 2 purple, 1 red"
+        );
+        assert!(query_feasible_bags(input, "4").is_err());
         Ok(())
     }
 }
@@ -0,0 +1,133 @@
+use common::parse::LineError;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Number {
+    pub value: u32,
+    pub row: usize,
+    // Columns start..end, end exclusive
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub kind: char,
+    pub row: usize,
+    pub col: usize,
+}
+
+// Bipartite graph of the numbers and symbols, linked where they touch (diagonals included).
+// Anything that isn't a digit or a `.` counts as a symbol.
+#[derive(Debug, Default)]
+pub struct Schematic {
+    pub numbers: Vec<Number>,
+    pub symbols: Vec<Symbol>,
+    // Indices into `symbols` for each number, and into `numbers` for each symbol
+    pub number_links: Vec<Vec<usize>>,
+    pub symbol_links: Vec<Vec<usize>>,
+}
+
+impl Schematic {
+    pub fn new(input: &str) -> Result<Schematic, LineError> {
+        let mut schematic = Schematic::default();
+        let width = input.lines().next().map_or(0, |line| line.chars().count());
+        for (row, line) in input.lines().enumerate() {
+            let error = |reason| LineError::new(row + 1, line, reason);
+            if line.chars().count() != width {
+                return Err(error("line length doesn't match the first line"));
+            }
+            let mut digits = String::new();
+            for (col, c) in line.chars().chain(std::iter::once('.')).enumerate() {
+                if c.is_ascii_digit() {
+                    digits.push(c);
+                    continue;
+                }
+                if !digits.is_empty() {
+                    schematic.numbers.push(Number {
+                        value: digits.parse().map_err(|_| error("number is too large"))?,
+                        row,
+                        start: col - digits.len(),
+                        end: col,
+                    });
+                    digits.clear();
+                }
+                if c != '.' && !c.is_whitespace() {
+                    schematic.symbols.push(Symbol { kind: c, row, col });
+                }
+            }
+        }
+        schematic.link();
+        Ok(schematic)
+    }
+
+    fn link(&mut self) {
+        let positions: HashMap<(usize, usize), usize> = self
+            .symbols
+            .iter()
+            .enumerate()
+            .map(|(index, symbol)| ((symbol.row, symbol.col), index))
+            .collect();
+        self.number_links = vec![Vec::new(); self.numbers.len()];
+        self.symbol_links = vec![Vec::new(); self.symbols.len()];
+        for (number_index, number) in self.numbers.iter().enumerate() {
+            for row in number.row.saturating_sub(1)..=number.row + 1 {
+                for col in number.start.saturating_sub(1)..=number.end {
+                    if let Some(symbol_index) = positions.get(&(row, col)) {
+                        self.number_links[number_index].push(*symbol_index);
+                        self.symbol_links[*symbol_index].push(number_index);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn symbol_kinds(&self) -> Vec<char> {
+        let mut kinds: Vec<char> = self.symbols.iter().map(|symbol| symbol.kind).collect();
+        kinds.sort_unstable();
+        kinds.dedup();
+        kinds
+    }
+
+    // Numbers touching exactly `count` symbols
+    pub fn numbers_touching(&self, count: usize) -> impl Iterator<Item = &Number> {
+        self.numbers
+            .iter()
+            .zip(&self.number_links)
+            .filter(move |(_, links)| links.len() == count)
+            .map(|(number, _)| number)
+    }
+
+    pub fn part_numbers(&self) -> impl Iterator<Item = &Number> {
+        self.numbers
+            .iter()
+            .zip(&self.number_links)
+            .filter(|(_, links)| !links.is_empty())
+            .map(|(number, _)| number)
+    }
+
+    // Symbols of the given kind touching exactly two numbers, with the product of those numbers
+    pub fn gear_ratios(&self, kind: char) -> impl Iterator<Item = (&Symbol, u64)> {
+        self.symbols
+            .iter()
+            .zip(&self.symbol_links)
+            .filter(move |(symbol, links)| symbol.kind == kind && links.len() == 2)
+            .map(|(symbol, links)| {
+                let ratio = links
+                    .iter()
+                    .map(|index| self.numbers[*index].value as u64)
+                    .product();
+                (symbol, ratio)
+            })
+    }
+
+    pub fn largest_gear_ratios(&self) -> HashMap<char, u64> {
+        self.symbol_kinds()
+            .into_iter()
+            .filter_map(|kind| {
+                let largest = self.gear_ratios(kind).map(|(_, ratio)| ratio).max()?;
+                Some((kind, largest))
+            })
+            .collect()
+    }
+}
@@ -1,203 +1,50 @@
 use anyhow::Result;
-use phf::{phf_set, Set};
-use std::collections::HashMap;
-use std::fmt::Error;
+use common::parse::LineError;
+use day_03::Schematic;
+use thiserror::Error;
 
-// Standard ascii symbols in PSF (perfect hash function) static set
-// I feel like treating everything like ASCII violates the sprit of
-// Rust's UTF-8 default, hence the set rather than just a set of if's
-// on the integer value of the char
-static SYMBOLS: Set<char> = phf_set! {
-    '!',
-    '"',
-    '#',
-    '$',
-    '%',
-    '&',
-    '\'',
-    '(',
-    ')',
-    '*',
-    '+',
-    ',',
-    '-',
-    '/',
-    ':',
-    ';',
-    '<',
-    '=',
-    '>',
-    '?',
-    '@',
-    '[',
-    '\\',
-    ']',
-    '^',
-    '_',
-    '`',
-    '{',
-    '|',
-    '}',
-    '~',
-};
-
-static MOVES: [[i32; 2]; 8] = [
-    [-1, 0],
-    [1, 0],
-    [0, 1],
-    [0, -1],
-    [1, 1],
-    [1, -1],
-    [-1, 1],
-    [-1, -1],
-];
-
-fn is_symbol(c: char) -> bool {
-    SYMBOLS.contains(&c)
+#[derive(Debug, Error)]
+pub enum AoCError {
+    #[error("Unable to parse the input `{0}`")]
+    ParsingError(String),
+    #[error(transparent)]
+    LineError(#[from] LineError),
+    #[error("An unknown error has occurred (super duper helpful error)")]
+    Unknown,
 }
 
 fn main() {
     let input: &str = include_str!("./input.txt");
-    println!("Part 1 result: {}", process_input_part_1(input).unwrap());
-    println!("Part 2 result: {}", process_input_part_2(input).unwrap());
-}
-
-fn get_sym_locations(input: &str, sym_locations: &mut HashMap<(i32, i32), char>) {
-    input.lines().enumerate().for_each(|(num, line)| {
-        line.chars()
-            .enumerate()
-            .filter(|(_, c)| is_symbol(*c))
-            .for_each(|(index, c)| {
-                sym_locations.insert((index.try_into().unwrap(), num.try_into().unwrap()), c);
-            })
-    });
-}
-
-fn is_connected(x: i32, y: i32, sym_locations: &mut HashMap<(i32, i32), char>) -> bool {
-    for direction in MOVES.iter() {
-        let next_x = x + direction[0];
-        let next_y = y + direction[1];
-        if sym_locations.contains_key(&(next_x, next_y)) {
-            return true;
-        }
-    }
-    false
-}
-
-fn get_unconnected_nums(
-    num: usize,
-    line: &str,
-    sym_locations: &mut HashMap<(i32, i32), char>,
-) -> Result<u32, Error> {
-    let mut result: u32 = 0;
-    let mut found_num: u32 = 0;
-    let mut connected: bool = false;
-    for (index, c) in line.chars().enumerate() {
-        if c.is_numeric() {
-            if !connected {
-                let y: i32 = num.try_into().unwrap();
-                let x: i32 = index.try_into().unwrap();
-                connected = is_connected(x, y, sym_locations);
-            }
-            found_num = (found_num * 10) + c.to_digit(10).unwrap();
-        } else {
-            if connected && found_num > 0 {
-                result += found_num;
-            }
-            found_num = 0;
-            connected = false;
-        }
+    match process_input_part_1(input) {
+        Ok(result) => println!("Part 1 result: {}", result),
+        Err(e) => println!("Error: {}", e),
     }
-    if connected && found_num > 0 {
-        result += found_num;
+    match process_input_part_2(input) {
+        Ok(result) => println!("Part 2 result: {}", result),
+        Err(e) => println!("Error: {}", e),
     }
-    Ok(result)
 }
 
-fn process_input_part_1(input: &str) -> Result<u32, Error> {
-    let mut sym_locations: HashMap<(i32, i32), char> = HashMap::new();
-    get_sym_locations(input, &mut sym_locations);
-    let count = input
-        .lines()
-        .enumerate()
-        .map(|(num, line)| get_unconnected_nums(num, line, &mut sym_locations).unwrap())
-        .sum();
+fn process_input_part_1(input: &str) -> Result<u32, AoCError> {
+    let schematic = Schematic::new(input)?;
+    let count = schematic.part_numbers().map(|number| number.value).sum();
     Ok(count)
 }
 
-fn get_gear_points(input: &str, gear_points: &mut HashMap<(i32, i32), Vec<u32>>) {
-    input.lines().enumerate().for_each(|(num, line)| {
-        line.chars()
-            .enumerate()
-            .filter(|(_, c)| *c == '*')
-            .for_each(|(index, _)| {
-                gear_points.insert(
-                    (index.try_into().unwrap(), num.try_into().unwrap()),
-                    Vec::<u32>::new(),
-                );
-            })
-    });
-}
-
-fn is_geared(x: i32, y: i32, gear_points: &mut HashMap<(i32, i32), Vec<u32>>) -> (bool, i32, i32) {
-    for direction in MOVES.iter() {
-        let next_x = x + direction[0];
-        let next_y = y + direction[1];
-        if gear_points.contains_key(&(next_x, next_y)) {
-            return (true, next_x, next_y);
-        }
-    }
-    (false, 0, 0)
-}
-
-fn connect_nums_to_gears(num: usize, line: &str, gear_points: &mut HashMap<(i32, i32), Vec<u32>>) {
-    let mut found_num: u32 = 0;
-    let mut connected: bool = false;
-    let mut gear_x = 0;
-    let mut gear_y = 0;
-    for (index, c) in line.chars().enumerate() {
-        if c.is_numeric() {
-            if !connected {
-                let y: i32 = num.try_into().unwrap();
-                let x: i32 = index.try_into().unwrap();
-                (connected, gear_x, gear_y) = is_geared(x, y, gear_points);
-            }
-            found_num = (found_num * 10) + c.to_digit(10).unwrap();
-        } else {
-            if connected {
-                if let Some(nums) = gear_points.get_mut(&(gear_x, gear_y)) {
-                    nums.push(found_num);
-                }
-            }
-            found_num = 0;
-            connected = false;
-        }
-    }
-    if connected {
-        if let Some(nums) = gear_points.get_mut(&(gear_x, gear_y)) {
-            nums.push(found_num);
-        }
-    }
-}
-
-fn process_input_part_2(input: &str) -> Result<u32, Error> {
-    let mut gear_points: HashMap<(i32, i32), Vec<u32>> = HashMap::new();
-    get_gear_points(input, &mut gear_points);
-    input.lines().enumerate().for_each(|(num, line)| {
-        connect_nums_to_gears(num, line, &mut gear_points);
-    });
-    let count: u32 = gear_points
-        .iter()
-        .filter(|(_, nums)| nums.len() == 2)
-        .map(|(_, nums)| nums.iter().product::<u32>())
-        .sum();
-
-    Ok(count)
+fn process_input_part_2(input: &str) -> Result<u32, AoCError> {
+    let schematic = Schematic::new(input)?;
+    let count = schematic
+        .gear_ratios('*')
+        .map(|(_, ratio)| ratio)
+        .sum::<u64>();
+    u32::try_from(count)
+        .map_err(|_| AoCError::ParsingError(format!("gear ratios sum to {}", count)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use day_03::Number;
     #[test]
     fn test_1() -> Result<()> {
         let input_1 = "467..114..
@@ -228,4 +75,50 @@ mod tests {
         assert_eq!(process_input_part_2(input_2).unwrap(), 467835);
         Ok(())
     }
+
+    #[test]
+    fn graph_queries() -> Result<()> {
+        let input = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+        let schematic = Schematic::new(input).unwrap();
+        assert_eq!(schematic.symbol_kinds(), vec!['#', '$', '*', '+']);
+        assert_eq!(schematic.numbers.len(), 10);
+        assert_eq!(
+            schematic.numbers[0],
+            Number {
+                value: 467,
+                row: 0,
+                start: 0,
+                end: 3
+            }
+        );
+        let loners: Vec<u32> = schematic.numbers_touching(0).map(|n| n.value).collect();
+        assert_eq!(loners, vec![114, 58]);
+        let largest = schematic.largest_gear_ratios();
+        assert_eq!(largest.get(&'*'), Some(&451490));
+        assert_eq!(largest.get(&'#'), None);
+
+        // Symbols come from the input, so anything unusual still counts
+        let truncated = Schematic::new("467..114..\n...*......\n..35").unwrap_err();
+        assert_eq!((truncated.line, truncated.text.as_str()), (3, "..35"));
+        let huge = process_input_part_1("1.99999999999.").unwrap_err();
+        assert!(matches!(
+            huge,
+            AoCError::LineError(LineError { line: 1, .. })
+        ));
+
+        let odd = Schematic::new("1.2\n.é.\n3..").unwrap();
+        assert_eq!(odd.symbols[0].kind, 'é');
+        assert_eq!(odd.numbers_touching(1).count(), 3);
+        assert_eq!(odd.gear_ratios('é').count(), 0);
+        Ok(())
+    }
 }
@@ -0,0 +1,162 @@
+use common::parse::LineError;
+use lazy_static::lazy_static;
+use num::{CheckedAdd, One, Zero};
+use regex::Regex;
+use std::collections::{HashSet, VecDeque};
+use std::io::BufRead;
+use thiserror::Error;
+
+lazy_static! {
+    static ref NUMBER_MATCH: Regex = Regex::new(r"(\d+)").unwrap();
+}
+
+#[derive(Debug, Error)]
+pub enum StreamError {
+    #[error("Unable to read the cards: {0}")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Parse(#[from] LineError),
+    #[error("The totals overflow at line {line}")]
+    Overflow { line: usize },
+}
+
+fn parse_numbers(input: &str) -> Result<Vec<i32>, &'static str> {
+    input
+        .split_whitespace()
+        .map(|number| {
+            number
+                .parse::<i32>()
+                .map_err(|_| "expected a list of numbers")
+        })
+        .collect()
+}
+
+pub fn get_matches(input: &str) -> Result<usize, &'static str> {
+    let (card, numbers) = input
+        .split_once(':')
+        .ok_or("missing `:` after the card id")?;
+    if !NUMBER_MATCH.is_match(card) {
+        return Err("expected `Card <id>`");
+    }
+    let (plays, win_nums) = numbers
+        .split_once('|')
+        .ok_or("missing `|` between the two lists of numbers")?;
+    let winning_numbers: HashSet<i32> = parse_numbers(win_nums)?.into_iter().collect();
+    let mut wins = 0;
+    for number in parse_numbers(plays)? {
+        if winning_numbers.contains(&number) {
+            wins += 1;
+        }
+    }
+    Ok(wins)
+}
+
+// 2^(matches - 1), or nothing for a card without matches
+fn points<T: Clone + Zero + One + CheckedAdd>(matches: usize) -> Option<T> {
+    if matches == 0 {
+        return Some(T::zero());
+    }
+    let mut result = T::one();
+    for _ in 1..matches {
+        result = result.checked_add(&result)?;
+    }
+    Some(result)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Card<T> {
+    pub number: usize,
+    pub matches: usize,
+    pub copies: T,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tally<T> {
+    pub cards: usize,
+    pub points: T,
+    pub copies: T,
+}
+
+// Counts copies as cards arrive, without knowing how many there will be.
+// `pending[i]` holds the copies already won of the card `i + 1` places after the last one,
+// so the buffer never grows past the most matches on a single card.
+pub struct CardPile<T> {
+    pending: VecDeque<T>,
+    tally: Tally<T>,
+}
+
+impl<T: Clone + Zero + One + CheckedAdd> Default for CardPile<T> {
+    fn default() -> Self {
+        CardPile::new()
+    }
+}
+
+impl<T: Clone + Zero + One + CheckedAdd> CardPile<T> {
+    pub fn new() -> CardPile<T> {
+        CardPile {
+            pending: VecDeque::new(),
+            tally: Tally {
+                cards: 0,
+                points: T::zero(),
+                copies: T::zero(),
+            },
+        }
+    }
+
+    // Adds the next card, returning None if a count no longer fits in `T`.
+    // Copies won of cards past the end of the pile are simply never collected.
+    pub fn push(&mut self, matches: usize) -> Option<Card<T>> {
+        let won = self.pending.pop_front().unwrap_or_else(T::zero);
+        let copies = won.checked_add(&T::one())?;
+        if self.pending.len() < matches {
+            self.pending.resize(matches, T::zero());
+        }
+        for pending in self.pending.iter_mut().take(matches) {
+            *pending = pending.checked_add(&copies)?;
+        }
+        self.tally.cards += 1;
+        self.tally.points = self.tally.points.checked_add(&points(matches)?)?;
+        self.tally.copies = self.tally.copies.checked_add(&copies)?;
+        Some(Card {
+            number: self.tally.cards,
+            matches,
+            copies,
+        })
+    }
+
+    pub fn buffered(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn tally(&self) -> &Tally<T> {
+        &self.tally
+    }
+}
+
+// Reads one card per line, skipping blank lines, and hands each to `visit` once its copies are known
+pub fn stream_cards<T: Clone + Zero + One + CheckedAdd>(
+    mut reader: impl BufRead,
+    mut visit: impl FnMut(&Card<T>),
+) -> Result<Tally<T>, StreamError> {
+    let mut pile = CardPile::new();
+    let mut line = String::new();
+    let mut line_number = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        line_number += 1;
+        let text = line.trim_end();
+        if text.trim().is_empty() {
+            continue;
+        }
+        let matches =
+            get_matches(text).map_err(|reason| LineError::new(line_number, text, reason))?;
+        let card = pile
+            .push(matches)
+            .ok_or(StreamError::Overflow { line: line_number })?;
+        visit(&card);
+    }
+    Ok(pile.tally)
+}
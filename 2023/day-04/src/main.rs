@@ -1,66 +1,104 @@
 use anyhow::Result;
-use lazy_static::lazy_static;
-use regex::Regex;
-use std::{collections::HashSet, fmt::Error};
+use common::parse::LineError;
+use day_04::{stream_cards, Card, StreamError, Tally};
+use num::{BigUint, CheckedAdd, One, Zero};
+use std::fmt::Display;
+use std::io::BufRead;
+use thiserror::Error;
 
-lazy_static! {
-    static ref NUMBER_MATCH: Regex = Regex::new(r"(\d+)").unwrap();
+#[derive(Debug, Error)]
+pub enum AoCError {
+    #[error("Unable to parse the input `{0}`")]
+    ParsingError(String),
+    #[error(transparent)]
+    LineError(#[from] LineError),
+    #[error("Unable to read the cards `{0}`")]
+    IoError(String),
+    #[error("The card counts overflow at line {0}")]
+    Overflow(usize),
+    #[error("An unknown error has occurred (super duper helpful error)")]
+    Unknown,
+}
+
+impl From<StreamError> for AoCError {
+    fn from(error: StreamError) -> Self {
+        match error {
+            StreamError::Io(e) => AoCError::IoError(e.to_string()),
+            StreamError::Parse(e) => AoCError::LineError(e),
+            StreamError::Overflow { line } => AoCError::Overflow(line),
+        }
+    }
 }
 
 fn main() {
     let input: &str = include_str!("./input.txt");
     println!("\n🎄🎄🎄🎄🎄 Advent of Code ||| Day 04 🎄🎄🎄🎄🎄\n");
-    println!("Part 1 result\n\t{}\n", process_part_1(input).unwrap());
-    println!("Part 2 result\n\t{}", process_part_2(input).unwrap());
-}
-
-fn get_matches(input: &str) -> Result<i32, Error> {
-    let numbers = input.split(':').nth(1).unwrap();
-    let (plays, win_nums) = numbers.split_once('|').unwrap();
-    let mut winning_numbers: HashSet<i32> = HashSet::new();
-    for capture in NUMBER_MATCH.captures_iter(win_nums) {
-        let number = capture[1].parse::<i32>().unwrap();
-        winning_numbers.insert(number);
+    // Usage: day-04 [--stdin] [--copies] [--big]
+    let mut from_stdin = false;
+    let mut print_copies = false;
+    let mut big = false;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--stdin" => from_stdin = true,
+            "--copies" => print_copies = true,
+            "--big" => big = true,
+            _ => println!("Ignoring unknown argument `{}`", arg),
+        }
     }
-    let mut wins = 0;
-    for capture in NUMBER_MATCH.captures_iter(plays) {
-        let number = capture[1].parse::<i32>().unwrap();
-        if winning_numbers.contains(&number) {
-            wins += 1;
+    if !(from_stdin || print_copies || big) {
+        match process_part_1(input) {
+            Ok(result) => println!("Part 1 result\n\t{}\n", result),
+            Err(e) => println!("Error: {}", e),
+        }
+        match process_part_2(input) {
+            Ok(result) => println!("Part 2 result\n\t{}", result),
+            Err(e) => println!("Error: {}", e),
         }
+        return;
+    }
+    let result = match (from_stdin, big) {
+        (true, true) => report::<BigUint>(std::io::stdin().lock(), print_copies),
+        (true, false) => report::<u128>(std::io::stdin().lock(), print_copies),
+        (false, true) => report::<BigUint>(input.as_bytes(), print_copies),
+        (false, false) => report::<u128>(input.as_bytes(), print_copies),
+    };
+    if let Err(e) = result {
+        println!("Error: {}", e);
     }
-    Ok(wins)
 }
 
-fn process_part_1(input: &str) -> Result<i32, Error> {
-    let result = input
-        .lines()
-        .map(|line| get_matches(line).unwrap())
-        .filter(|x| *x > 0)
-        .map(|x| 1 << (x - 1))
-        .sum();
-    Ok(result)
+// u128 covers a few hundred cards that each win the next two; past that use BigUint
+fn report<T: Clone + Zero + One + CheckedAdd + Display>(
+    reader: impl BufRead,
+    print_copies: bool,
+) -> Result<(), AoCError> {
+    let tally: Tally<T> = stream_cards(reader, |card: &Card<T>| {
+        if print_copies {
+            println!(
+                "Card {}: {} matches, {} copies",
+                card.number, card.matches, card.copies
+            );
+        }
+    })?;
+    println!("Part 1 result\n\t{}\n", tally.points);
+    println!("Part 2 result\n\t{}", tally.copies);
+    Ok(())
 }
 
-fn process_part_2(input: &str) -> Result<i32, Error> {
-    let number_cards = input.lines().count();
-    let mut card_count = vec![1; number_cards];
-    for (num, line) in input.lines().enumerate() {
-        let mut wins = get_matches(line).unwrap();
-        let mut index = num + 1;
-        while wins > 0 && index < number_cards {
-            card_count[index] += card_count[num];
-            index += 1;
-            wins -= 1;
-        }
-    }
-    let result = card_count.iter().sum();
-    Ok(result)
+fn process_part_1(input: &str) -> Result<u128, AoCError> {
+    let tally: Tally<u128> = stream_cards(input.as_bytes(), |_| {})?;
+    Ok(tally.points)
+}
+
+fn process_part_2(input: &str) -> Result<u128, AoCError> {
+    let tally: Tally<u128> = stream_cards(input.as_bytes(), |_| {})?;
+    Ok(tally.copies)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use day_04::CardPile;
     #[test]
     fn test_1() -> Result<()> {
         let input_1 = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
@@ -83,4 +121,57 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
         assert_eq!(process_part_2(input_2).unwrap(), 30);
         Ok(())
     }
+    #[test]
+    fn malformed_input() {
+        let truncated = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61";
+        let error = process_part_1(truncated).unwrap_err();
+        assert!(matches!(
+            error,
+            AoCError::LineError(LineError { line: 2, .. })
+        ));
+        assert!(error.to_string().contains("missing `|`"));
+        assert!(process_part_2("Card 1: 41 x8 | 83").is_err());
+        assert!(process_part_2("41 48 | 83").is_err());
+    }
+    #[test]
+    fn streaming_copies() -> Result<()> {
+        let input = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
+";
+        let mut copies = Vec::new();
+        let tally = stream_cards(input.as_bytes(), |card: &Card<u64>| {
+            copies.push(card.copies)
+        })?;
+        assert_eq!(copies, vec![1, 2, 4, 8, 14, 1]);
+        assert_eq!(tally.cards, 6);
+        assert_eq!((tally.points, tally.copies), (13, 30));
+
+        let mut pile = CardPile::<u32>::new();
+        for matches in [4, 2, 2, 1, 0, 0] {
+            pile.push(matches);
+            assert!(pile.buffered() <= 4);
+        }
+        Ok(())
+    }
+    #[test]
+    fn huge_piles() -> Result<()> {
+        // Every card wins a copy of the next two, so the copies grow like the Fibonacci numbers
+        let pile: String = (1..=400)
+            .map(|id| format!("Card {}: 1 2 | 1 2 3\n", id))
+            .collect();
+        let error = process_part_2(&pile).unwrap_err();
+        assert!(matches!(error, AoCError::Overflow(183)));
+
+        let tally: Tally<BigUint> = stream_cards(pile.as_bytes(), |_| {})?;
+        assert_eq!(tally.cards, 400);
+        assert_eq!(tally.points, BigUint::from(800u32));
+        assert!(tally.copies.bits() > 270);
+        Ok(())
+    }
 }
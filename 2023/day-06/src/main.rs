@@ -1,5 +1,7 @@
 use anyhow::Result;
+use common::math::isqrt;
 use lazy_static::lazy_static;
+use num::BigUint;
 use regex::Regex;
 use thiserror::Error;
 
@@ -13,6 +15,8 @@ lazy_static! {
 pub enum AoCError {
     #[error("Unable to parse the input `{0}`")]
     ParsingError(String),
+    #[error("The number of ways to win doesn't fit in the race's integer type")]
+    Overflow,
     #[error("An unknown error has occurred (super duper helpful error)")]
     Unknown,
 }
@@ -52,10 +56,7 @@ fn parse_races(
             Ok(x) => x,
             _ => return Err(AoCError::ParsingError(distances)),
         };
-        races.push(Race {
-            time,
-            distance: distance,
-        });
+        races.push(Race { time, distance });
     }
     Ok(races)
 }
@@ -76,36 +77,72 @@ fn get_races_kerning(input: &str) -> Result<Vec<Race<i64>>, AoCError> {
     let time_line = iter.next().unwrap();
     let dist_line = iter.next().unwrap();
 
-    let numbers = time_line.split(':').nth(1).unwrap().replace(" ", "");
-    let distances = dist_line.split(':').nth(1).unwrap().replace(" ", "");
+    let numbers = time_line.split(':').nth(1).unwrap().replace(' ', "");
+    let distances = dist_line.split(':').nth(1).unwrap().replace(' ', "");
     let races = parse_races(numbers, distances)?;
     Ok(races)
 }
 
-fn get_way_to_win(race: &Race<i64>) -> i64 {
+// A product too big for a u128 is certainly further than any u128 distance
+fn is_win(hold: u128, time: u128, distance: u128) -> bool {
+    hold.checked_mul(time - hold)
+        .is_none_or(|travelled| travelled > distance)
+}
+
+fn get_way_to_win<T>(race: &Race<T>) -> Result<T, AoCError>
+where
+    T: Copy + TryInto<u128> + TryFrom<u128>,
+{
     // Standard form: -x^2 + Time*x - Dist > 0
     // This is the number of integer points on the parabola > 0
-    // Thanks to the answer from Hagen von Eitzen
-    // https://math.stackexchange.com/questions/1867236/number-of-integers-between-two-real-numbers
-    let time = race.time as f64;
-    let dist = race.distance as f64;
-    let sqrt = ((-dist + (time / 2.).powi(2)) * 4.).sqrt();
-    let left = (-sqrt + time) / 2.;
-    let right = (sqrt + time) / 2.;
-    let count = (right.ceil() - left.floor() - 1.) as i64;
-    count.max(0)
+    // Negative times or distances don't fit in a u128, and nobody wins those races
+    let (Ok(time), Ok(dist)) = (race.time.try_into(), race.distance.try_into()) else {
+        return T::try_from(0).map_err(|_| AoCError::Overflow);
+    };
+    let overflow = || AoCError::Overflow;
+    // The roots are (time ± sqrt(time² - 4 dist)) / 2. Past 2^64 time² no longer fits in a
+    // u128, so then the discriminant is worked out with big integers instead.
+    let root = match (time.checked_mul(time), dist.checked_mul(4)) {
+        (Some(time_squared), Some(dist_4)) if time_squared > dist_4 => {
+            isqrt(time_squared - dist_4).map_err(|_| overflow())?
+        }
+        (Some(_), _) => return T::try_from(0).map_err(|_| overflow()),
+        (None, _) => {
+            let time_squared = BigUint::from(time).pow(2);
+            let dist_4 = BigUint::from(dist) * 4u32;
+            if time_squared <= dist_4 {
+                return T::try_from(0).map_err(|_| overflow());
+            }
+            // No bigger than the time, so it fits back in a u128
+            u128::try_from((time_squared - dist_4).sqrt()).map_err(|_| overflow())?
+        }
+    };
+    // The square root is rounded down, so the first winning hold can be a step or two
+    // either side of this
+    let mut first = (time - root) / 2;
+    while first > 0 && is_win(first - 1, time, dist) {
+        first -= 1;
+    }
+    while first <= time / 2 && !is_win(first, time, dist) {
+        first += 1;
+    }
+    // Symmetric around time / 2, so the last winning hold is time - first
+    let count = if first <= time / 2 {
+        time - 2 * first + 1
+    } else {
+        0
+    };
+    T::try_from(count).map_err(|_| overflow())
 }
 
 fn process_part_1(input: &str) -> Result<i64, AoCError> {
     let races = get_races(input)?;
-    let result = races.iter().map(|race| get_way_to_win(race)).product();
-    Ok(result)
+    races.iter().map(get_way_to_win).product()
 }
 
 fn process_part_2(input: &str) -> Result<i64, AoCError> {
     let races = get_races_kerning(input)?;
-    let result = races.iter().map(|race| get_way_to_win(race)).product();
-    Ok(result)
+    races.iter().map(get_way_to_win).product()
 }
 
 #[cfg(test)]
@@ -119,4 +156,92 @@ Distance:  9  40  200";
         assert_eq!(process_part_2(input_1)?, 71503);
         Ok(())
     }
+    fn brute_force(time: u64, distance: u64) -> u64 {
+        (0..=time)
+            .filter(|hold| hold * (time - hold) > distance)
+            .count() as u64
+    }
+
+    #[test]
+    fn exact_roots() -> Result<()> {
+        // Holding 10 or 20 on a 30 race goes exactly 200, which only ties the record
+        let race = Race {
+            time: 30i64,
+            distance: 200,
+        };
+        assert_eq!(get_way_to_win(&race)?, 9);
+        let tie = Race {
+            time: 10u32,
+            distance: 25,
+        };
+        assert_eq!(get_way_to_win(&tie)?, 0);
+        let no_record = Race {
+            time: 4u8,
+            distance: 0,
+        };
+        assert_eq!(get_way_to_win(&no_record)?, 3);
+        let impossible = Race {
+            time: 7i32,
+            distance: 13,
+        };
+        assert_eq!(get_way_to_win(&impossible)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn beyond_f64_precision() -> Result<()> {
+        // Roots at exactly 2^20 and 2^40, past the point where f64 keeps every integer of time²
+        let (low, high) = (1u64 << 20, 1u64 << 40);
+        let race = Race {
+            time: low + high,
+            distance: low * high,
+        };
+        assert_eq!(get_way_to_win(&race)?, high - low - 1);
+        let race = Race {
+            time: u64::MAX,
+            distance: 0,
+        };
+        assert_eq!(get_way_to_win(&race)?, u64::MAX - 1);
+        // Past 2^64 time² no longer fits in a u128, the count still does
+        let (low, high) = (1u128 << 30, 1u128 << 90);
+        let race = Race {
+            time: low + high,
+            distance: low * high,
+        };
+        assert_eq!(get_way_to_win(&race)?, high - low - 1);
+        let race = Race {
+            time: i128::MAX,
+            distance: 1,
+        };
+        assert_eq!(get_way_to_win(&race)?, i128::MAX - 1);
+        let race = Race {
+            time: u128::MAX,
+            distance: u128::MAX,
+        };
+        // Holding 1 only goes u128::MAX - 1, from 2 up to u128::MAX - 2 goes further than a u128
+        assert_eq!(get_way_to_win(&race)?, u128::MAX - 3);
+        Ok(())
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        fn property(time: u16, distance: u32) -> bool {
+            let (time, distance) = (time as u64 % 2000, distance as u64 % 1_000_000);
+            let race = Race { time, distance };
+            get_way_to_win(&race).ok() == Some(brute_force(time, distance))
+        }
+        quickcheck::quickcheck(property as fn(u16, u32) -> bool);
+
+        // Every race whose record is hit exactly by some hold
+        for time in 0..200u64 {
+            for hold in 0..=time {
+                let distance = hold * (time - hold);
+                let race = Race { time, distance };
+                assert_eq!(
+                    get_way_to_win(&race).ok(),
+                    Some(brute_force(time, distance))
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,141 @@
+use std::collections::{BTreeSet, HashMap};
+
+// A hand's shape is how many of each card it holds, largest group first: a full house is [3, 2].
+pub type Shape = Vec<usize>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleSet {
+    // Weakest card first
+    order: Vec<char>,
+    wild: Vec<char>,
+    hand_size: usize,
+    // Best shape first. Shapes that aren't listed rank below all the listed ones.
+    // Without a ranking shapes compare group by group, which is the usual poker order.
+    shape_ranking: Option<Vec<Shape>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Hand {
+    tier: usize,
+    shape: Shape,
+    ranks: Vec<usize>,
+    cards: String,
+}
+
+impl Hand {
+    pub fn shape(&self) -> &Shape {
+        &self.shape
+    }
+
+    pub fn cards(&self) -> &str {
+        &self.cards
+    }
+}
+
+impl RuleSet {
+    // `order` lists the cards weakest first, `wild` the ones that stand in for any other card
+    pub fn new(order: &str, wild: &str, hand_size: usize) -> Result<RuleSet, String> {
+        let order: Vec<char> = order.chars().collect();
+        let wild: Vec<char> = wild.chars().collect();
+        if order.iter().collect::<BTreeSet<_>>().len() != order.len() {
+            return Err(format!("`{}` repeats a card", String::from_iter(&order)));
+        }
+        if let Some(card) = wild.iter().find(|card| !order.contains(card)) {
+            return Err(format!("wild card `{}` isn't in the card order", card));
+        }
+        if hand_size == 0 {
+            return Err("hands need at least one card".to_string());
+        }
+        Ok(RuleSet {
+            order,
+            wild,
+            hand_size,
+            shape_ranking: None,
+        })
+    }
+
+    pub fn standard() -> RuleSet {
+        RuleSet::new("23456789TJQKA", "", 5).expect("the standard rules are valid")
+    }
+
+    // Jacks become jokers: the weakest card, but wild
+    pub fn jokers() -> RuleSet {
+        RuleSet::new("J23456789TQKA", "J", 5).expect("the joker rules are valid")
+    }
+
+    pub fn with_shape_ranking(mut self, ranking: Vec<Shape>) -> RuleSet {
+        self.shape_ranking = Some(ranking);
+        self
+    }
+
+    fn tier(&self, shape: &Shape) -> usize {
+        match &self.shape_ranking {
+            Some(ranking) => ranking
+                .iter()
+                .position(|ranked| ranked == shape)
+                .map_or(0, |index| ranking.len() - index),
+            None => 0,
+        }
+    }
+
+    // Every shape the wild cards can make, each one joining a group or starting a new one
+    fn shapes(mut groups: Shape, wild: usize, result: &mut BTreeSet<Shape>) {
+        groups.sort_unstable_by(|a, b| b.cmp(a));
+        if wild == 0 {
+            result.insert(groups);
+            return;
+        }
+        for index in 0..groups.len() {
+            // Growing either of two equal groups makes the same shape
+            if index > 0 && groups[index] == groups[index - 1] {
+                continue;
+            }
+            let mut next = groups.clone();
+            next[index] += 1;
+            RuleSet::shapes(next, wild - 1, result);
+        }
+        let mut next = groups;
+        next.push(1);
+        RuleSet::shapes(next, wild - 1, result);
+    }
+
+    pub fn hand(&self, cards: &str) -> Result<Hand, String> {
+        let mut ranks = Vec::with_capacity(self.hand_size);
+        let mut counts: HashMap<char, usize> = HashMap::new();
+        let mut wild = 0;
+        for card in cards.chars() {
+            let rank = self
+                .order
+                .iter()
+                .position(|known| *known == card)
+                .ok_or_else(|| format!("unknown card `{}` in `{}`", card, cards))?;
+            ranks.push(rank);
+            if self.wild.contains(&card) {
+                wild += 1;
+            } else {
+                *counts.entry(card).or_default() += 1;
+            }
+        }
+        if ranks.len() != self.hand_size {
+            return Err(format!(
+                "`{}` has {} cards, expected {}",
+                cards,
+                ranks.len(),
+                self.hand_size
+            ));
+        }
+        let mut shapes = BTreeSet::new();
+        RuleSet::shapes(counts.into_values().collect(), wild, &mut shapes);
+        let (tier, shape) = shapes
+            .into_iter()
+            .map(|shape| (self.tier(&shape), shape))
+            .max()
+            .expect("a hand has at least one shape");
+        Ok(Hand {
+            tier,
+            shape,
+            ranks,
+            cards: cards.to_string(),
+        })
+    }
+}
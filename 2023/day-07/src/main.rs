@@ -1,4 +1,5 @@
 use anyhow::Result;
+use day_07::{Hand, RuleSet};
 use itertools::Itertools;
 use lazy_static::lazy_static;
 use regex::Regex;
@@ -18,120 +19,19 @@ pub enum AoCError {
     Unknown,
 }
 
-//#[derive(Copy, Clone, Debug, Error)]
-pub struct Points {
-    hand: i32,
-    mult: i64,
-}
-
 fn main() {
     let input: &str = include_str!("./input.txt");
     println!(
         "\n🎄🎄🎄🎄🎄 Advent of Code ||| Day {} 🎄🎄🎄🎄🎄\n",
         AOC_DAY
     );
-    println!("Part 1 result\n\t{}\n", process_part_1(input).unwrap());
-    println!("Part 2 result\n\t{}", process_part_2(input).unwrap());
-}
-
-fn card_to_points_1(c: char) -> i32 {
-    match c {
-        '2' => 1,
-        '3' => 2,
-        '4' => 3,
-        '5' => 4,
-        '6' => 5,
-        '7' => 6,
-        '8' => 7,
-        '9' => 8,
-        'T' => 9,
-        'J' => 10,
-        'Q' => 11,
-        'K' => 12,
-        'A' => 13,
-        _ => 0,
-    }
-}
-
-fn card_to_points_2(c: char) -> i32 {
-    match c {
-        'J' => 1,
-        '2' => 2,
-        '3' => 3,
-        '4' => 4,
-        '5' => 5,
-        '6' => 6,
-        '7' => 7,
-        '8' => 8,
-        '9' => 9,
-        'T' => 10,
-        'Q' => 11,
-        'K' => 12,
-        'A' => 13,
-        _ => 0,
-    }
-}
-
-fn calc_points_1(hand: &str, multiplier: i64) -> Points {
-    let mut hand_score = hand
-        .chars()
-        .map(card_to_points_1)
-        .rev()
-        .enumerate()
-        .map(|(pos, card)| card << (pos * 4))
-        .sum();
-    let card_counts = hand
-        .chars()
-        .counts()
-        .into_values()
-        .map(|num| num as i32)
-        .sorted_unstable_by(|a, b| b.cmp(a))
-        .collect::<Vec<i32>>();
-    hand_score += match (card_counts[0], card_counts.get(1)) {
-        (5, _) => 6 << 20,
-        (4, _) => 5 << 20,
-        (3, Some(x)) => (x + 2) << 20,
-        (2, Some(x)) => x << 20,
-        _ => 0,
-    };
-    Points {
-        hand: hand_score,
-        mult: multiplier,
+    match process_part_1(input) {
+        Ok(result) => println!("Part 1 result\n\t{}\n", result),
+        Err(e) => println!("Error: {}", e),
     }
-}
-
-fn calc_points_2(hand: &str, multiplier: i64) -> Points {
-    let mut hand_score = hand
-        .chars()
-        .map(card_to_points_2)
-        .rev()
-        .enumerate()
-        .map(|(pos, card)| card << (pos * 4))
-        .sum();
-    let mut initial_counts = hand.chars().counts();
-    let num_jokers = match initial_counts.remove(&'J') {
-        Some(x) => x as i32,
-        _ => 0,
-    };
-    let card_counts = initial_counts
-        .into_values()
-        .map(|num| num as i32)
-        .sorted_unstable_by(|a, b| b.cmp(a))
-        .collect::<Vec<i32>>();
-    let highest = match card_counts.first() {
-        Some(x) => *x,
-        _ => 0,
-    };
-    hand_score += match (highest + num_jokers, card_counts.get(1)) {
-        (5, _) => 6 << 20,
-        (4, _) => 5 << 20,
-        (3, Some(x)) => (x + 2) << 20,
-        (2, Some(x)) => x << 20,
-        _ => 0,
-    };
-    Points {
-        hand: hand_score,
-        mult: multiplier,
+    match process_part_2(input) {
+        Ok(result) => println!("Part 2 result\n\t{}", result),
+        Err(e) => println!("Error: {}", e),
     }
 }
 
@@ -144,31 +44,37 @@ fn get_hand(input: &str) -> Result<(&str, i64), AoCError> {
         Some(x) => x,
         _ => return Err(AoCError::ParsingError(mult.to_string())),
     };
-    let multiplier = capture[1].parse::<i64>().unwrap();
+    let multiplier = capture[1]
+        .parse::<i64>()
+        .map_err(|_| AoCError::ParsingError(mult.to_string()))?;
     Ok((hand, multiplier))
 }
 
-fn process(input: &str, calc: fn(&str, i64) -> Points) -> Result<i64, AoCError> {
-    let points = input
+fn process(input: &str, rules: &RuleSet) -> Result<i64, AoCError> {
+    let hands = input
         .lines()
-        .map(|line| get_hand(line).unwrap())
-        .map(|(hand, mult)| calc(hand, mult))
-        .sorted_unstable_by(|a, b| a.hand.cmp(&b.hand))
-        .collect::<Vec<Points>>();
-    let result = points
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (hand, mult) = get_hand(line)?;
+            let hand = rules.hand(hand).map_err(AoCError::ParsingError)?;
+            Ok((hand, mult))
+        })
+        .collect::<Result<Vec<(Hand, i64)>, AoCError>>()?;
+    let result = hands
         .iter()
+        .sorted_unstable_by(|a, b| a.0.cmp(&b.0))
         .enumerate()
-        .map(|(pos, points)| ((pos as i64) + 1) * points.mult)
+        .map(|(pos, (_, mult))| ((pos as i64) + 1) * mult)
         .sum();
     Ok(result)
 }
 
 fn process_part_1(input: &str) -> Result<i64, AoCError> {
-    process(input, calc_points_1)
+    process(input, &RuleSet::standard())
 }
 
 fn process_part_2(input: &str) -> Result<i64, AoCError> {
-    process(input, calc_points_2)
+    process(input, &RuleSet::jokers())
 }
 
 #[cfg(test)]
@@ -185,4 +91,36 @@ QQQJA 483";
         assert_eq!(process_part_2(input_1)?, 5905);
         Ok(())
     }
+    #[test]
+    fn rule_variants() -> Result<()> {
+        let standard = RuleSet::standard();
+        let jokers = RuleSet::jokers();
+        assert_eq!(standard.hand("KTJJT").unwrap().shape(), &vec![2, 2, 1]);
+        assert_eq!(jokers.hand("KTJJT").unwrap().shape(), &vec![4, 1]);
+        assert_eq!(jokers.hand("JJJJJ").unwrap().shape(), &vec![5]);
+        assert!(jokers.hand("KJJ23").unwrap() > jokers.hand("KK234").unwrap());
+        assert!(standard.hand("KJJ23").unwrap() < standard.hand("KK234").unwrap());
+        assert!(standard.hand("KK677").unwrap() > standard.hand("KTJJT").unwrap());
+
+        // Ace low, with both deuces and threes wild
+        let ace_low = RuleSet::new("A23456789TJQK", "23", 5).unwrap();
+        assert_eq!(ace_low.hand("23AKQ").unwrap().shape(), &vec![3, 1, 1]);
+        assert!(ace_low.hand("A4455").unwrap() < ace_low.hand("K4455").unwrap());
+
+        // Three card hands where a pair outranks three of a kind
+        let short = RuleSet::new("23456789TJQKA", "J", 3)
+            .unwrap()
+            .with_shape_ranking(vec![vec![2, 1], vec![3]]);
+        assert_eq!(short.hand("J99").unwrap().shape(), &vec![2, 1]);
+        assert!(short.hand("992").unwrap() > short.hand("AAA").unwrap());
+        assert!(short.hand("AKQ").unwrap() < short.hand("AAA").unwrap());
+        assert_eq!(process("992 10\nAAA 1\n234 100", &short)?, 100 + 2 + 30);
+
+        assert!(standard.hand("AAAA").is_err());
+        assert!(standard.hand("AAAA1").is_err());
+        assert!(RuleSet::new("2345", "6", 5).is_err());
+        assert!(RuleSet::new("2245", "", 5).is_err());
+        assert!(process_part_1("32T3K").is_err());
+        Ok(())
+    }
 }
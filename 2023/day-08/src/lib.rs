@@ -0,0 +1,261 @@
+use common::math::{crt, MathError};
+use std::collections::HashMap;
+
+pub const LEFT: usize = 0;
+pub const RIGHT: usize = 1;
+
+// Hands out dense ids to labels in the order they're first seen
+#[derive(Debug, Default, Clone)]
+pub struct Interner {
+    ids: HashMap<String, usize>,
+    labels: Vec<String>,
+}
+
+impl Interner {
+    pub fn intern(&mut self, label: &str) -> usize {
+        if let Some(id) = self.ids.get(label) {
+            return *id;
+        }
+        self.labels.push(label.to_string());
+        self.ids.insert(label.to_string(), self.labels.len() - 1);
+        self.labels.len() - 1
+    }
+
+    pub fn get(&self, label: &str) -> Option<usize> {
+        self.ids.get(label).copied()
+    }
+
+    pub fn label(&self, id: usize) -> &str {
+        &self.labels[id]
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Network {
+    labels: Interner,
+    // Left and right successors, indexed by node id
+    next: Vec<[usize; 2]>,
+    // LEFT or RIGHT, repeated forever
+    moves: Vec<usize>,
+}
+
+fn parse_node(line: &str) -> Option<(&str, &str, &str)> {
+    let (source, destinations) = line.split_once('=')?;
+    let (left, right) = destinations
+        .trim()
+        .strip_prefix('(')?
+        .strip_suffix(')')?
+        .split_once(',')?;
+    let (source, left, right) = (source.trim(), left.trim(), right.trim());
+    if [source, left, right].iter().any(|label| label.is_empty()) {
+        return None;
+    }
+    Some((source, left, right))
+}
+
+impl Network {
+    // The first line holds the moves, followed by one `NODE = (LEFT, RIGHT)` per line
+    pub fn parse(input: &str) -> Result<Network, String> {
+        let mut lines = input.lines();
+        let moves = lines
+            .next()
+            .ok_or("missing the list of moves")?
+            .trim()
+            .chars()
+            .map(|c| match c {
+                'L' => Ok(LEFT),
+                'R' => Ok(RIGHT),
+                _ => Err(format!("unknown move `{}`", c)),
+            })
+            .collect::<Result<Vec<usize>, String>>()?;
+        if moves.is_empty() {
+            return Err("missing the list of moves".to_string());
+        }
+        let mut labels = Interner::default();
+        let mut next: Vec<Option<[usize; 2]>> = Vec::new();
+        for line in lines.filter(|line| !line.trim().is_empty()) {
+            let (source, left, right) = parse_node(line).ok_or(line.to_string())?;
+            let ids = [
+                labels.intern(source),
+                labels.intern(left),
+                labels.intern(right),
+            ];
+            next.resize(labels.len(), None);
+            if next[ids[0]].replace([ids[1], ids[2]]).is_some() {
+                return Err(format!("node `{}` is defined twice", source));
+            }
+        }
+        let next = next
+            .into_iter()
+            .enumerate()
+            .map(|(id, successors)| {
+                successors.ok_or_else(|| format!("node `{}` is never defined", labels.label(id)))
+            })
+            .collect::<Result<Vec<[usize; 2]>, String>>()?;
+        Ok(Network {
+            labels,
+            next,
+            moves,
+        })
+    }
+
+    pub fn id(&self, label: &str) -> Option<usize> {
+        self.labels.get(label)
+    }
+
+    pub fn label(&self, id: usize) -> &str {
+        self.labels.label(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.next.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.next.is_empty()
+    }
+
+    pub fn moves(&self) -> &[usize] {
+        &self.moves
+    }
+
+    pub fn nodes_where(&self, predicate: impl Fn(&str) -> bool) -> Vec<usize> {
+        (0..self.len())
+            .filter(|id| predicate(self.label(*id)))
+            .collect()
+    }
+
+    // Where `node` leads on the `step`th move
+    pub fn step(&self, node: usize, step: usize) -> usize {
+        self.next[node][self.moves[step % self.moves.len()]]
+    }
+
+    // Steps from `start` until the first node matching `is_end`. After visiting every
+    // (node, move) pair without finding one it never will, so that gives None.
+    pub fn steps_until(&self, start: usize, is_end: impl Fn(usize) -> bool) -> Option<u64> {
+        let limit = self.len() as u64 * self.moves.len() as u64;
+        let mut current = start;
+        for count in 0..=limit {
+            if is_end(current) {
+                return Some(count);
+            }
+            current = self.step(current, count as usize);
+        }
+        None
+    }
+}
+
+// Where a walk settles into a loop. The walk is in the same state, the same node at the same
+// point in the moves, at `step` and `step + period` for every `step >= offset`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    pub offset: u64,
+    pub period: u64,
+    // Every step before `offset + period` that lands on an end node, in order
+    pub hits: Vec<u64>,
+}
+
+impl Cycle {
+    pub fn is_hit(&self, step: u64) -> bool {
+        let folded = match step < self.offset {
+            true => step,
+            false => self.offset + (step - self.offset) % self.period,
+        };
+        self.hits.binary_search(&folded).is_ok()
+    }
+}
+
+impl Network {
+    pub fn cycle(&self, start: usize, is_end: impl Fn(usize) -> bool) -> Cycle {
+        let moves = self.moves.len();
+        let mut seen: Vec<Option<u64>> = vec![None; self.len() * moves];
+        let mut hits = Vec::new();
+        let mut node = start;
+        let mut step: u64 = 0;
+        loop {
+            let state = node * moves + (step % moves as u64) as usize;
+            if let Some(offset) = seen[state] {
+                return Cycle {
+                    offset,
+                    period: step - offset,
+                    hits,
+                };
+            }
+            seen[state] = Some(step);
+            if is_end(node) {
+                hits.push(step);
+            }
+            node = self.step(node, (step % moves as u64) as usize);
+            step += 1;
+        }
+    }
+}
+
+// The first step at which every walk is on an end node at once. MathError::NoSolution when that
+// never happens.
+pub fn first_arrival(cycles: &[Cycle]) -> Result<u128, MathError> {
+    let settled = cycles
+        .iter()
+        .map(|cycle| cycle.offset)
+        .max()
+        .ok_or(MathError::NoSolution)?;
+    // Before the last walk settles into its loop, an arrival has to be one of its early hits
+    let mut early: Vec<u64> = cycles
+        .iter()
+        .flat_map(|cycle| cycle.hits.iter().copied())
+        .filter(|hit| *hit < settled)
+        .collect();
+    early.sort_unstable();
+    if let Some(step) = early
+        .into_iter()
+        .find(|step| cycles.iter().all(|cycle| cycle.is_hit(*step)))
+    {
+        return Ok(step as u128);
+    }
+
+    // After that each walk only arrives on the steps congruent to one of its looping hits
+    let mut systems: Vec<(i128, i128)> = vec![(0, 1)];
+    for cycle in cycles {
+        let period = cycle.period as i128;
+        let residues: Vec<i128> = cycle
+            .hits
+            .iter()
+            .filter(|hit| **hit >= cycle.offset)
+            .map(|hit| *hit as i128 % period)
+            .collect();
+        let mut combined = Vec::new();
+        for system in systems.iter() {
+            for residue in residues.iter() {
+                match crt([*system, (*residue, period)]) {
+                    Ok(solution) => combined.push(solution),
+                    Err(MathError::NoSolution) => continue,
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+        combined.sort_unstable();
+        combined.dedup();
+        if combined.is_empty() {
+            return Err(MathError::NoSolution);
+        }
+        systems = combined;
+    }
+    let settled = settled as i128;
+    systems
+        .into_iter()
+        .map(|(residue, modulus)| match residue < settled {
+            true => residue + (settled - residue + modulus - 1) / modulus * modulus,
+            false => residue,
+        })
+        .min()
+        .map(|step| step as u128)
+        .ok_or(MathError::NoSolution)
+}
@@ -1,5 +1,6 @@
-use std::{collections::HashMap, iter::Cycle, str::Chars};
 use anyhow::Result;
+use common::math::MathError;
+use day_08::{first_arrival, Cycle, Network};
 use thiserror::Error;
 
 static AOC_DAY: &str = "08";
@@ -8,6 +9,12 @@ static AOC_DAY: &str = "08";
 pub enum AoCError {
     #[error("Unable to parse the input `{0}`")]
     ParsingError(String),
+    #[error("No end node can be reached from `{0}`")]
+    NoPath(String),
+    #[error("The ghosts are never all on an end node at once")]
+    NoArrival,
+    #[error("Unable to combine the cycles, {0}")]
+    MathError(#[from] MathError),
     #[error("An unknown error has occurred (super duper helpful error)")]
     Unknown,
 }
@@ -28,116 +35,61 @@ fn main() {
     }
 }
 
-fn node_to_int(node: &str) -> Option<i32> {
-    if node.len() != 3 {
-        return None;
-    }
-    let mut value: i32 = 0;
-    node.bytes()
-        .rev()
-        .enumerate()
-        .for_each(|(index, c)| value += (c as i32) << (index * 8));
-    Some(value)
-}
-
-fn get_moves(input: &str) -> Option<Cycle<Chars<'_>>> {
-    Some(input.lines().next()?.chars().cycle())
-}
-
-fn parse_line(line: &str) -> Option<(i32, i32, i32)> {
-    let (mut source, destinations) = line.split_once('=')?;
-    let (mut left, mut right) = destinations.split_once(',')?;
-    source = source.trim();
-    left = left.trim().trim_matches('(');
-    right = right.trim().trim_matches(')');
-    let source_num = node_to_int(source)?;
-    let left_num = node_to_int(left)?;
-    let right_num = node_to_int(right)?;
-    Some((source_num, left_num, right_num))
-}
-
-fn get_map(input: &str) -> Result<HashMap<i32, (i32, i32)>, AoCError> {
-    let mut map: HashMap<i32, (i32, i32)> = HashMap::new();
-    for line in input.lines().skip(2) {
-        let (source_num, left_num, right_num) = match parse_line(line) {
-            Some(x) => x,
-             _ => return Err(AoCError::ParsingError(line.to_string())),
-        };
-        map.insert(source_num, (left_num, right_num));
-    }
-    Ok(map)
+fn parse_network(input: &str) -> Result<Network, AoCError> {
+    Network::parse(input).map_err(AoCError::ParsingError)
 }
 
 fn process_part_1(input: &str) -> Result<i64, AoCError> {
-    let mut moves = match get_moves(input) {
-        Some(x) => x,
-        _=> return Err(AoCError::ParsingError("Unable to parse first line".to_string())),
+    let network = parse_network(input)?;
+    let (start, end) = match (network.id("AAA"), network.id("ZZZ")) {
+        (Some(start), Some(end)) => (start, end),
+        _ => {
+            return Err(AoCError::ParsingError(
+                "missing node AAA or ZZZ".to_string(),
+            ))
+        }
     };
-    let map = get_map(input)?;
-    let mut result: i64 = 0;
-    let mut current = node_to_int("AAA").unwrap();
-    let destination = node_to_int("ZZZ").unwrap();
-    while current != destination {
-        let direction = moves.next().unwrap();
-        let next = match map.get(&current) {
-            Some(x) => x,
-            _ => return Err(AoCError::Unknown),
-        };
-        current = match direction {
-            'L' => next.0,
-            'R' => next.1,
-            _ => panic!("Unknown direction: {}", direction),
-        };
-        result += 1;
+    match network.steps_until(start, |node| node == end) {
+        Some(steps) => Ok(steps as i64),
+        None => Err(AoCError::NoPath(network.label(start).to_string())),
     }
-    Ok(result)
 }
 
-fn get_ends_with(c: char, map: &HashMap<i32, (i32, i32)>) -> Vec<i32> {
-    let value = c as i32;
-    let nodes: Vec<i32> = map
-        .keys()
-        .filter(|node| (**node & 0xFF) == value)
-        .copied()
+// Every ghost walks from its own start at once, until they're all on an end node together
+fn ghost_steps(
+    network: &Network,
+    is_start: impl Fn(&str) -> bool,
+    is_end: impl Fn(&str) -> bool,
+) -> Result<u128, AoCError> {
+    let starts = network.nodes_where(is_start);
+    if starts.is_empty() {
+        return Err(AoCError::ParsingError("no start nodes".to_string()));
+    }
+    let mut ends = vec![false; network.len()];
+    for end in network.nodes_where(is_end) {
+        ends[end] = true;
+    }
+    let cycles: Vec<Cycle> = starts
+        .iter()
+        .map(|start| network.cycle(*start, |node| ends[node]))
         .collect();
-    nodes
-}
-
-fn vec_lcm(nums: &[i64]) -> i64 {
-    if nums.len() == 1 {
-        return nums[0];
+    if let Some(index) = cycles.iter().position(|cycle| cycle.hits.is_empty()) {
+        return Err(AoCError::NoPath(network.label(starts[index]).to_string()));
+    }
+    match first_arrival(&cycles) {
+        Ok(step) => Ok(step),
+        Err(MathError::NoSolution) => Err(AoCError::NoArrival),
+        Err(e) => Err(AoCError::MathError(e)),
     }
-    num::integer::lcm::<i64>(nums[0], vec_lcm(&nums[1..]))
 }
 
-fn process_part_2(input: &str) -> Result<i64, AoCError> {
-    let map = get_map(input)?;
-    let starting_positions = get_ends_with('A', &map);
-    let mut result: Vec<i64> = Vec::new();
-    let moves_start = match get_moves(input) {
-        Some(x) => x,
-        _=> return Err(AoCError::ParsingError("Unable to parse first line".to_string())),
-    };
-    for pos in starting_positions.iter() {
-        let mut current = *pos;
-        let mut count: i64 = 0;
-        let mut moves = moves_start.clone();
-        while (current & 0xFF) != ('Z' as i32) {
-            let direction = moves.next().unwrap();
-            let next = match map.get(&current) {
-                Some(x) => x,
-                _ => return Err(AoCError::Unknown),
-            };
-            current = match direction {
-                'L' => next.0,
-                'R' => next.1,
-                _ => panic!("Unknown direction: {}", direction),
-            };
-            count += 1;
-        }
-        result.push(count);
-    }
-    Ok(vec_lcm(&result))
+fn process_part_2(input: &str) -> Result<u128, AoCError> {
+    let network = parse_network(input)?;
+    ghost_steps(
+        &network,
+        |label| label.ends_with('A'),
+        |label| label.ends_with('Z'),
+    )
 }
 
 #[cfg(test)]
@@ -179,4 +131,96 @@ XXX = (XXX, XXX)";
         assert_eq!(process_part_2(input_2)?, 6);
         Ok(())
     }
+
+    #[test]
+    fn long_labels() -> Result<()> {
+        let input = "LR
+
+start-one = (middle, start-one)
+middle = (start-one, goal)
+goal = (middle, middle)
+other-start = (far-away, far-away)
+far-away = (goal, goal)
+";
+        let network = Network::parse(input).unwrap();
+        assert_eq!(network.len(), 5);
+        assert_eq!(network.label(network.id("far-away").unwrap()), "far-away");
+        let starts = network.nodes_where(|label| label.contains("start"));
+        assert_eq!(starts, vec![0, 3]);
+        assert_eq!(
+            ghost_steps(
+                &network,
+                |label| label.contains("start"),
+                |label| label == "goal"
+            )?,
+            2
+        );
+        let error = ghost_steps(
+            &network,
+            |label| label == "goal",
+            |label| label == "nowhere",
+        );
+        assert!(matches!(error, Err(AoCError::NoPath(_))));
+
+        assert!(Network::parse("LR\n\nAAA = (BBB, CCC)").is_err());
+        assert!(Network::parse("LX\n\nAAA = (AAA, AAA)").is_err());
+        assert!(Network::parse("LR\n\nAAA = AAA, AAA").is_err());
+        assert!(Network::parse("L\n\nA = (A, A)\nA = (A, A)").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn ghost_cycles() -> Result<()> {
+        let input = "L
+
+AAA = (AB1, AB1)
+AB1 = (ABZ, ABZ)
+ABZ = (AB3, AB3)
+AB3 = (AB1, AB1)
+BBA = (BBZ, BBZ)
+BBZ = (BB2, BB2)
+BB2 = (BBZ, BBZ)
+CCA = (CC1, CC1)
+CC1 = (CCZ, CCZ)
+CCZ = (CC1, CC1)
+DDA = (DD1, DD1)
+DD1 = (DDZ, DDZ)
+DDZ = (DEZ, DEZ)
+DEZ = (DD1, DD1)
+EEA = (EEZ, EEZ)
+EEZ = (EE2, EE2)
+EE2 = (EE2, EE2)
+";
+        let network = Network::parse(input).unwrap();
+        let is_end = |node: usize| network.label(node).ends_with('Z');
+        let cycle = |label: &str| network.cycle(network.id(label).unwrap(), is_end);
+        assert_eq!(
+            cycle("AAA"),
+            Cycle {
+                offset: 1,
+                period: 3,
+                hits: vec![2]
+            }
+        );
+        assert_eq!(cycle("DDA").hits, vec![2, 3]);
+        assert_eq!(cycle("EEA").offset, 2);
+        assert!(cycle("BBA").is_hit(101));
+
+        let solve = |starts: &[&str]| {
+            ghost_steps(
+                &network,
+                |label| starts.contains(&label),
+                |label| label.ends_with('Z'),
+            )
+        };
+        // The first hits are 2 and 1, but their lcm misses the offsets
+        assert_eq!(solve(&["AAA", "BBA"])?, 5);
+        assert_eq!(solve(&["AAA", "DDA"])?, 2);
+        assert_eq!(solve(&["DDA", "BBA"])?, 3);
+        // EEA only ever arrives before it settles into its loop
+        assert_eq!(solve(&["EEA", "BBA"])?, 1);
+        assert!(matches!(solve(&["EEA", "CCA"]), Err(AoCError::NoArrival)));
+        assert!(matches!(solve(&["BBA", "CCA"]), Err(AoCError::NoArrival)));
+        Ok(())
+    }
 }
@@ -0,0 +1,122 @@
+use num::{BigInt, BigRational, One, Signed, ToPrimitive, Zero};
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SequenceError {
+    #[error("the sequence is empty")]
+    Empty,
+    #[error("the differences never reach a row of zeros, so the degree can't be told")]
+    NoZeroRow,
+    #[error("the value doesn't fit in an i64")]
+    Overflow,
+}
+
+// The polynomial through a sequence, with values[i] = p(i), in Newton's forward form:
+// p(n) = sum of differences[k] * C(n, k)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    differences: Vec<BigInt>,
+}
+
+// C(n, k) = n (n - 1) ... (n - k + 1) / k!, which is also defined for negative n
+fn binomial(n: &BigInt, k: usize) -> BigInt {
+    let mut result = BigInt::one();
+    for i in 0..k {
+        result = result * (n - BigInt::from(i)) / BigInt::from(i + 1);
+    }
+    result
+}
+
+impl Polynomial {
+    // The lowest degree polynomial through `values`. The differences have to reach a row of
+    // zeros, otherwise a higher degree could fit just as well.
+    pub fn fit(values: &[i64]) -> Result<Polynomial, SequenceError> {
+        if values.is_empty() {
+            return Err(SequenceError::Empty);
+        }
+        let mut row: Vec<BigInt> = values.iter().map(|value| BigInt::from(*value)).collect();
+        let mut differences = Vec::new();
+        while !row.iter().all(Zero::is_zero) {
+            if row.len() == 1 {
+                return Err(SequenceError::NoZeroRow);
+            }
+            differences.push(row[0].clone());
+            row = row.windows(2).map(|pair| &pair[1] - &pair[0]).collect();
+        }
+        Ok(Polynomial { differences })
+    }
+
+    // The zero polynomial has degree 0 here, like any other constant
+    pub fn degree(&self) -> usize {
+        self.differences.len().saturating_sub(1)
+    }
+
+    pub fn predict(&self, n: i64) -> BigInt {
+        let n = BigInt::from(n);
+        self.differences
+            .iter()
+            .enumerate()
+            .map(|(k, difference)| difference * binomial(&n, k))
+            .sum()
+    }
+
+    pub fn predict_i64(&self, n: i64) -> Result<i64, SequenceError> {
+        self.predict(n).to_i64().ok_or(SequenceError::Overflow)
+    }
+
+    // Coefficients of 1, n, n², ..., lowest power first
+    pub fn coefficients(&self) -> Vec<BigRational> {
+        let mut result = vec![BigRational::zero(); self.degree() + 1];
+        // n (n - 1) ... (n - k + 1) expanded, lowest power first
+        let mut falling = vec![BigInt::one()];
+        let mut factorial = BigInt::one();
+        for (k, difference) in self.differences.iter().enumerate() {
+            if k > 0 {
+                factorial *= BigInt::from(k);
+                let mut next = vec![BigInt::zero(); falling.len() + 1];
+                for (power, coefficient) in falling.iter().enumerate() {
+                    next[power + 1] += coefficient;
+                    next[power] -= coefficient * BigInt::from(k - 1);
+                }
+                falling = next;
+            }
+            for (power, coefficient) in falling.iter().enumerate() {
+                result[power] += BigRational::new(difference * coefficient, factorial.clone());
+            }
+        }
+        result
+    }
+}
+
+// Written out with the highest power first, like `1/2 n^2 + 1/2 n`
+pub fn format_polynomial(coefficients: &[BigRational]) -> String {
+    let terms: Vec<String> = coefficients
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(_, coefficient)| !coefficient.is_zero())
+        .map(|(power, coefficient)| {
+            let magnitude = coefficient.abs();
+            let sign = if coefficient.is_negative() { "-" } else { "+" };
+            let value = match (power, magnitude.is_one()) {
+                (0, _) => magnitude.to_string(),
+                (_, true) => String::new(),
+                _ => format!("{} ", magnitude),
+            };
+            let variable = match power {
+                0 => String::new(),
+                1 => "n".to_string(),
+                _ => format!("n^{}", power),
+            };
+            format!("{} {}{}", sign, value, variable)
+        })
+        .collect();
+    if terms.is_empty() {
+        return "0".to_string();
+    }
+    let joined = terms.join(" ");
+    match joined.strip_prefix("+ ") {
+        Some(positive) => positive.to_string(),
+        None => joined.replacen("- ", "-", 1),
+    }
+}
@@ -1,4 +1,6 @@
 use anyhow::Result;
+use day_09::{format_polynomial, Polynomial, SequenceError};
+use num::{BigInt, ToPrimitive};
 use thiserror::Error;
 
 static AOC_DAY: &str = "09";
@@ -7,6 +9,10 @@ static AOC_DAY: &str = "09";
 pub enum AoCError {
     #[error("Unable to parse the input `{0}`")]
     ParsingError(String),
+    #[error("Unable to extrapolate line {line}, {reason}")]
+    SequenceError { line: usize, reason: SequenceError },
+    #[error("The result `{0}` doesn't fit in an i64")]
+    Overflow(BigInt),
     #[error("An unknown error has occurred (super duper helpful error)")]
     Unknown,
 }
@@ -25,6 +31,27 @@ fn main() {
         Ok(result) => println!("Part 2 result\n\t{}", result),
         Err(e) => println!("Error: {}", e),
     }
+    // Usage: day-09 [--polynomials] [--at <index>]
+    let mut polynomials = false;
+    let mut index = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--polynomials" => polynomials = true,
+            "--at" => index = args.next().and_then(|x| x.parse::<i64>().ok()),
+            _ => println!("Ignoring unknown argument `{}`", arg),
+        }
+    }
+    if polynomials {
+        if let Err(e) = print_polynomials(INPUT, index) {
+            println!("Error: {}", e);
+        }
+    } else if let Some(index) = index {
+        match sum_at(INPUT, |_| index) {
+            Ok(result) => println!("\nSum of the values at {}\n\t{}", index, result),
+            Err(e) => println!("Error: {}", e),
+        }
+    }
 }
 
 fn parse_line(input: &str) -> Result<Vec<i64>, AoCError> {
@@ -49,58 +76,56 @@ fn parse_input(input: &str) -> Result<Vec<Vec<i64>>, AoCError> {
     Ok(output)
 }
 
-fn build_nums(numbers: &[i64]) -> Vec<Vec<i64>> {
-    let mut triangle: Vec<Vec<i64>> = Vec::new();
-    triangle.push(numbers.to_vec());
-    let mut check: i64 = 1;
-    while check != 0 {
-        check = 0;
-        let next = triangle
-            .last()
-            .unwrap()
-            .windows(2)
-            .map(|nums| {
-                let num = nums[1] - nums[0];
-                check |= num;
-                num
+fn fit_lines(input: &str) -> Result<Vec<Polynomial>, AoCError> {
+    parse_input(input)?
+        .iter()
+        .enumerate()
+        .map(|(line, numbers)| {
+            Polynomial::fit(numbers).map_err(|reason| AoCError::SequenceError {
+                line: line + 1,
+                reason,
             })
-            .collect();
-        if check != 0 {
-            triangle.push(next);
-        }
-    }
-    triangle
+        })
+        .collect()
 }
 
-fn extrapolate_forward(numbers: &[i64]) -> i64 {
-    let triangle = build_nums(numbers);
-    let value = triangle
+// Sum of every line's value at `index`, where the first value given is at index 0
+fn sum_at(input: &str, index: impl Fn(usize) -> i64) -> Result<BigInt, AoCError> {
+    let input = input.trim();
+    let lengths = input.lines().map(|line| line.split_whitespace().count());
+    Ok(fit_lines(input)?
         .iter()
-        .map(|line| line.last().unwrap())
-        .sum::<i64>();
-    value
+        .zip(lengths)
+        .map(|(polynomial, length)| polynomial.predict(index(length)))
+        .sum())
 }
 
-fn extrapolate_back(numbers: &[i64]) -> i64 {
-    let triangle = build_nums(numbers);
-    let mut last = 0;
-    triangle.iter().rev().for_each(|line| {
-        let value = line.first().unwrap() - last;
-        last = value;
-    });
-    last
+fn to_i64(value: BigInt) -> Result<i64, AoCError> {
+    value.to_i64().ok_or(AoCError::Overflow(value))
 }
 
 fn process_part_1(input: &str) -> Result<i64, AoCError> {
-    let numbers = parse_input(input)?;
-    let result = numbers.iter().map(|line| extrapolate_forward(line)).sum();
-    Ok(result)
+    to_i64(sum_at(input, |length| length as i64)?)
 }
 
 fn process_part_2(input: &str) -> Result<i64, AoCError> {
-    let numbers = parse_input(input)?;
-    let result = numbers.iter().map(|line| extrapolate_back(line)).sum();
-    Ok(result)
+    to_i64(sum_at(input, |_| -1)?)
+}
+
+fn print_polynomials(input: &str, index: Option<i64>) -> Result<(), AoCError> {
+    for (line, polynomial) in fit_lines(input.trim())?.iter().enumerate() {
+        print!(
+            "Line {}: degree {}, p(n) = {}",
+            line + 1,
+            polynomial.degree(),
+            format_polynomial(&polynomial.coefficients())
+        );
+        match index {
+            Some(index) => println!(", p({}) = {}", index, polynomial.predict(index)),
+            None => println!(),
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -124,4 +149,41 @@ mod tests {
         assert_eq!(process_part_2(input_2)?, 2);
         Ok(())
     }
+    #[test]
+    fn polynomials() -> Result<()> {
+        let squares = Polynomial::fit(&[1, 4, 9, 16, 25])?;
+        assert_eq!(squares.degree(), 2);
+        assert_eq!(format_polynomial(&squares.coefficients()), "n^2 + 2 n + 1");
+        let far = 1_000_000_000i64;
+        assert_eq!(squares.predict(far), BigInt::from(far + 1).pow(2));
+        assert_eq!(squares.predict_i64(-3)?, 4);
+
+        let triangular = Polynomial::fit(&[0, 1, 3, 6, 10, 15])?;
+        assert_eq!(
+            format_polynomial(&triangular.coefficients()),
+            "1/2 n^2 + 1/2 n"
+        );
+        let falling = Polynomial::fit(&[7, 5, 3, 1])?;
+        assert_eq!(format_polynomial(&falling.coefficients()), "-2 n + 7");
+        let zero = Polynomial::fit(&[0, 0])?;
+        assert_eq!(
+            (zero.degree(), format_polynomial(&zero.coefficients())),
+            (0, "0".to_string())
+        );
+
+        let cubes = Polynomial::fit(&[0, 1, 8, 27, 64])?;
+        assert_eq!(cubes.predict_i64(10_000_000), Err(SequenceError::Overflow));
+        assert_eq!(
+            Polynomial::fit(&[1, 2, 4, 8, 16]),
+            Err(SequenceError::NoZeroRow)
+        );
+        assert_eq!(Polynomial::fit(&[]), Err(SequenceError::Empty));
+        let error = process_part_1("1 2 3\n1 2 4 8").unwrap_err();
+        assert!(matches!(error, AoCError::SequenceError { line: 2, .. }));
+        assert_eq!(
+            sum_at("0 3 6 9 12 15\n1 3 6 10 15 21", |_| 100)?,
+            BigInt::from(300 + 5151)
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,241 @@
+use common::geometry::Point;
+use thiserror::Error;
+
+pub mod render;
+
+pub const NORTH: usize = 0;
+pub const EAST: usize = 1;
+pub const SOUTH: usize = 2;
+pub const WEST: usize = 3;
+
+pub const MOVES: [[i64; 2]; 4] = [
+    // N - E - S - W
+    [0, -1],
+    [1, 0],
+    [0, 1],
+    [-1, 0],
+];
+
+const PIPES: &str = "|-LJ7F";
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MazeError {
+    #[error("the maze is empty")]
+    Empty,
+    #[error("row {row} is {width} tiles wide, expected {expected}")]
+    Ragged {
+        row: usize,
+        width: usize,
+        expected: usize,
+    },
+    #[error("unknown tile `{tile}` at {at:?}")]
+    UnknownTile { tile: char, at: Point },
+    #[error("there's no `S` to start from")]
+    NoStart,
+    #[error("there's more than one `S`, at {0:?} and {1:?}")]
+    ManyStarts(Point, Point),
+    #[error("only {0} pipes lead into `S`, a loop needs two")]
+    DeadEnd(usize),
+    #[error("no pair of the pipes leading into `S` closes a loop")]
+    NoLoop,
+    #[error("the loop runs off the edge of the maze at {0:?}")]
+    OffEdge(Point),
+    #[error("`{pipe}` at {at:?} doesn't connect back to the pipe before it")]
+    Disconnected { pipe: char, at: Point },
+}
+
+pub fn opposite_direction(dir: usize) -> usize {
+    (dir + 2) % 4
+}
+
+// The two directions a pipe leads in, or None for ground and `S`
+pub fn connections(pipe: char) -> Option<[usize; 2]> {
+    match pipe {
+        '|' => Some([NORTH, SOUTH]),
+        '-' => Some([EAST, WEST]),
+        'L' => Some([NORTH, EAST]),
+        'J' => Some([NORTH, WEST]),
+        '7' => Some([SOUTH, WEST]),
+        'F' => Some([EAST, SOUTH]),
+        _ => None,
+    }
+}
+
+pub fn pipe_between(a: usize, b: usize) -> Option<char> {
+    PIPES
+        .chars()
+        .find(|pipe| connections(*pipe).is_some_and(|dirs| dirs == [a.min(b), a.max(b)]))
+}
+
+fn step(pos: Point, dir: usize) -> Point {
+    (pos.0 + MOVES[dir][0], pos.1 + MOVES[dir][1])
+}
+
+#[derive(Debug, Clone)]
+pub struct Maze {
+    // Indexed by row then column
+    tiles: Vec<Vec<char>>,
+    start: Point,
+}
+
+// The main loop, every tile in walking order from `S`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PipeLoop {
+    pub start_pipe: char,
+    pub tiles: Vec<Point>,
+}
+
+impl PipeLoop {
+    // The farthest any tile is from `S` along the loop
+    pub fn farthest(&self) -> usize {
+        self.tiles.len() / 2
+    }
+
+    // Each tile with its pipe, the one under `S` included
+    pub fn pipes<'a>(&'a self, maze: &'a Maze) -> impl Iterator<Item = (Point, char)> + 'a {
+        self.tiles.iter().map(move |pos| match *pos == maze.start {
+            true => (*pos, self.start_pipe),
+            false => (*pos, maze.get(*pos).expect("loop tiles are in the maze")),
+        })
+    }
+
+    // The corners of the loop, in walking order
+    pub fn vertices(&self, maze: &Maze) -> Vec<Point> {
+        self.pipes(maze)
+            .filter(|(_, pipe)| !matches!(pipe, '|' | '-'))
+            .map(|(pos, _)| pos)
+            .collect()
+    }
+}
+
+impl Maze {
+    pub fn parse(input: &str) -> Result<Maze, MazeError> {
+        let tiles: Vec<Vec<char>> = input
+            .lines()
+            .map(|line| line.trim().chars().collect())
+            .filter(|line: &Vec<char>| !line.is_empty())
+            .collect();
+        let width = tiles.first().ok_or(MazeError::Empty)?.len();
+        let mut start = None;
+        for (row, line) in tiles.iter().enumerate() {
+            if line.len() != width {
+                return Err(MazeError::Ragged {
+                    row,
+                    width: line.len(),
+                    expected: width,
+                });
+            }
+            for (col, tile) in line.iter().enumerate() {
+                let at = (col as i64, row as i64);
+                match tile {
+                    'S' => match start {
+                        Some(first) => return Err(MazeError::ManyStarts(first, at)),
+                        None => start = Some(at),
+                    },
+                    // The puzzle's examples mark some of the ground as inside or outside
+                    '.' | 'I' | 'O' => (),
+                    tile if PIPES.contains(*tile) => (),
+                    tile => return Err(MazeError::UnknownTile { tile: *tile, at }),
+                }
+            }
+        }
+        Ok(Maze {
+            tiles,
+            start: start.ok_or(MazeError::NoStart)?,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.tiles[0].len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn start(&self) -> Point {
+        self.start
+    }
+
+    pub fn get(&self, pos: Point) -> Option<char> {
+        let row = self.tiles.get(usize::try_from(pos.1).ok()?)?;
+        row.get(usize::try_from(pos.0).ok()?).copied()
+    }
+
+    // Follows the pipes from `S`, leaving towards `out`, until the walk comes back into `S`
+    // from the direction `back`
+    fn walk(&self, out: usize, back: usize) -> Result<Vec<Point>, MazeError> {
+        let mut tiles = vec![self.start];
+        let mut pos = step(self.start, out);
+        let mut from = opposite_direction(out);
+        loop {
+            if pos == self.start {
+                return match from == back {
+                    true => Ok(tiles),
+                    false => Err(MazeError::NoLoop),
+                };
+            }
+            let pipe = self.get(pos).ok_or(MazeError::OffEdge(pos))?;
+            let dirs = connections(pipe)
+                .filter(|dirs| dirs.contains(&from))
+                .ok_or(MazeError::Disconnected { pipe, at: pos })?;
+            tiles.push(pos);
+            let next = if dirs[0] == from { dirs[1] } else { dirs[0] };
+            pos = step(pos, next);
+            from = opposite_direction(next);
+        }
+    }
+
+    // Which of the pipes next to `S` lead into it, and of those the pair that closes a loop.
+    // Junk pipes can point at `S` too, so more than two leading in isn't an error by itself.
+    pub fn main_loop(&self) -> Result<PipeLoop, MazeError> {
+        let leading_in: Vec<usize> = (0..4)
+            .filter(|dir| {
+                self.get(step(self.start, *dir))
+                    .and_then(connections)
+                    .is_some_and(|dirs| dirs.contains(&opposite_direction(*dir)))
+            })
+            .collect();
+        if leading_in.len() < 2 {
+            return Err(MazeError::DeadEnd(leading_in.len()));
+        }
+        let mut error = MazeError::NoLoop;
+        for (index, out) in leading_in.iter().enumerate() {
+            for back in leading_in[index + 1..].iter() {
+                match self.walk(*out, *back) {
+                    Ok(tiles) => {
+                        return Ok(PipeLoop {
+                            start_pipe: pipe_between(*out, *back)
+                                .expect("two directions make a pipe"),
+                            tiles,
+                        })
+                    }
+                    // Keep the most telling reason in case no pair works
+                    Err(e) if leading_in.len() == 2 => error = e,
+                    Err(_) => (),
+                }
+            }
+        }
+        Err(error)
+    }
+}
+
+// A tile once the main loop is known. Tiles off the loop keep what the input had there,
+// `.` or a junk pipe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tile {
+    Loop(char),
+    Inside(char),
+    Outside(char),
+}
+
+// Indexed by row then column
+pub type TileMap = Vec<Vec<Tile>>;
+
+pub fn count_inside(tiles: &TileMap) -> usize {
+    tiles
+        .iter()
+        .flatten()
+        .filter(|tile| matches!(tile, Tile::Inside(_)))
+        .count()
+}
@@ -1,203 +1,24 @@
 use anyhow::Result;
+use common::geometry::{Point, Polygon, Winding};
+use day_10::render::{ansi_map, plain_map, write_svg};
+use day_10::{
+    opposite_direction, Maze, MazeError, PipeLoop, Tile, TileMap, EAST, MOVES, NORTH, SOUTH, WEST,
+};
 use queue::Queue;
+use std::fs::File;
+use std::io::BufWriter;
 use thiserror::Error;
 
 const AOC_DAY: &str = "10";
 
-const MOVES: [[i32; 2]; 4] = [
-    // N - E - S - W
-    [0, -1],
-    [1, 0],
-    [0, 1],
-    [-1, 0],
-];
-
-const CONNECT: [[char; 4]; 4] = [
-    ['|', 'F', '7', 'S'],
-    ['-', '7', 'J', 'S'],
-    ['|', 'L', 'J', 'S'],
-    ['-', 'L', 'F', 'S'],
-];
-
-const NORTH: usize = 0;
-const EAST: usize = 1;
-const SOUTH: usize = 2;
-const WEST: usize = 3;
-
-#[derive(Copy, Clone, PartialEq, Eq)]
-enum MoveDirection {
-    Forward,
-    Backward,
-}
-
-fn next_direction(current: char, prev: usize) -> Option<usize> {
-    match current {
-        '|' => match prev {
-            NORTH => Some(SOUTH),
-            SOUTH => Some(NORTH),
-            _ => None,
-        },
-        '-' => match prev {
-            WEST => Some(EAST),
-            EAST => Some(WEST),
-            _ => None,
-        },
-        'L' => match prev {
-            NORTH => Some(EAST),
-            EAST => Some(NORTH),
-            _ => None,
-        },
-        'J' => match prev {
-            NORTH => Some(WEST),
-            WEST => Some(NORTH),
-            _ => None,
-        },
-        '7' => match prev {
-            WEST => Some(SOUTH),
-            SOUTH => Some(WEST),
-            _ => None,
-        },
-        'F' => match prev {
-            EAST => Some(SOUTH),
-            SOUTH => Some(EAST),
-            _ => None,
-        },
-        _ => None,
-    }
-}
-
-fn opposite_direction(dir: usize) -> usize {
-    (dir + 2) % 4
-}
-
-#[derive(Copy, Clone)]
-struct Maze<'a> {
-    data: &'a [u8],
-    width: usize,
-    height: usize,
-}
-
-impl Maze<'_> {
-    pub fn new(data: &str) -> Maze {
-        let height = data.lines().count();
-        let width = data.lines().next().unwrap().chars().count();
-        Maze {
-            data: data.as_bytes(),
-            width,
-            height,
-        }
-    }
-    pub fn get(&self, x: i32, y: i32) -> Option<char> {
-        if x >= (self.width as i32) || y >= (self.height as i32) || x < 0 || y < 0 {
-            return None;
-        }
-        let index = ((y as usize) * (self.width + 1)) + (x as usize);
-        Some(self.data[index] as char)
-    }
-}
-
-#[derive(Copy, Clone)]
-struct MazePos<'a> {
-    maze: &'a Maze<'a>,
-    pos: (i32, i32),
-    prev_pos: (i32, i32),
-    start: (i32, i32),
-    prev: usize,
-    steps: usize,
-    move_direction: MoveDirection,
-}
-
-impl MazePos<'_> {
-    pub fn new<'a>(maze: &'a Maze<'a>, direction: MoveDirection) -> MazePos<'a> {
-        let index = match maze.data.iter().position(|&x| x == b'S') {
-            Some(x) => x as i32,
-            _ => panic!("Unable to find start position!"),
-        };
-        let h = index / ((maze.width as i32) + 1);
-        let w = index % ((maze.width as i32) + 1);
-        MazePos {
-            maze,
-            steps: 0,
-            pos: (w, h),
-            prev_pos: (-1, -1),
-            start: (w, h),
-            prev: 0,
-            move_direction: direction,
-        }
-    }
-
-    fn connect_start(&mut self, i: usize) -> Option<usize> {
-        let next_x = self.pos.0 + MOVES[i][0];
-        let next_y = self.pos.1 + MOVES[i][1];
-        let pipe = match self.maze.get(next_x, next_y) {
-            Some(x) => x,
-            _ => return None,
-        };
-        if CONNECT[i].contains(&pipe) {
-            self.prev = opposite_direction(i);
-            self.pos = (next_x, next_y);
-            self.steps += 1;
-            return Some(self.steps);
-        }
-        None
-    }
-
-    fn next_move(&mut self) {
-        let pipe = match self.maze.get(self.pos.0, self.pos.1) {
-            Some(x) => x,
-            _ => panic!("Current pos pipe not found??"),
-        };
-        let next_dir = match next_direction(pipe, self.prev) {
-            Some(x) => x,
-            _ => panic!("Somehow ended up on invalid pipe"),
-        };
-        let next_x = self.pos.0 + MOVES[next_dir][0];
-        let next_y = self.pos.1 + MOVES[next_dir][1];
-        let next_pipe = match self.maze.get(next_x, next_y) {
-            Some(x) => x,
-            _ => panic!("Next pipe at {}, {} is not valid", next_x, next_y),
-        };
-        if !CONNECT[next_dir].contains(&next_pipe) {
-            panic!("Next pipe {} at {}, {} is not a valid connection from ({}) @ {}, {} towards {}. prev: {}", next_pipe, next_x, next_y, pipe, self.pos.0, self.pos.1, next_dir, self.prev);
-        }
-        self.prev_pos = self.pos;
-        self.prev = opposite_direction(next_dir);
-        self.pos = (next_x, next_y);
-        self.steps += 1;
-    }
-
-    pub fn next(&mut self) -> usize {
-        if self.pos == self.start {
-            if self.move_direction == MoveDirection::Backward {
-                for i in (0..4).rev() {
-                    match self.connect_start(i) {
-                        Some(x) => return x,
-                        _ => continue,
-                    }
-                }
-            } else {
-                for i in 0..4 {
-                    match self.connect_start(i) {
-                        Some(x) => return x,
-                        _ => continue,
-                    }
-                }
-            }
-            panic!("Nothing connected to start position!");
-        }
-        self.next_move();
-        self.steps
-    }
-
-    pub fn get(&self) -> Option<char> {
-        self.maze.get(self.pos.0, self.pos.1)
-    }
-}
-
 #[derive(Debug, Error)]
 pub enum AoCError {
     #[error("Unable to parse the input `{0}`")]
     ParsingError(String),
+    #[error("Unable to follow the pipes: {0}")]
+    MazeError(#[from] MazeError),
+    #[error("Unable to render the loop `{0}`")]
+    RenderError(String),
     #[error("An unknown error has occurred (super duper helpful error)")]
     Unknown,
 }
@@ -208,6 +29,23 @@ fn main() {
         "\n🎄🎄🎄🎄🎄 Advent of Code ||| Day {} 🎄🎄🎄🎄🎄\n",
         AOC_DAY
     );
+    // Usage: day-10 [--render | --plain] [--svg <file>]
+    let mut render: Option<fn(&TileMap) -> String> = None;
+    let mut svg_path = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--render" => render = Some(ansi_map),
+            "--plain" => render = Some(plain_map),
+            "--svg" => svg_path = args.next(),
+            _ => println!("Ignoring unknown argument `{}`", arg),
+        }
+    }
+    if render.is_some() || svg_path.is_some() {
+        if let Err(e) = render_part_2(INPUT, render, svg_path) {
+            println!("Error: {}", e);
+        }
+    }
     match process_part_1(INPUT) {
         Ok(result) => println!("Part 1 result\n\t{}\n", result),
         Err(e) => println!("Error: {}", e),
@@ -216,24 +54,21 @@ fn main() {
         Ok(result) => println!("Part 2 result\n\t{}", result),
         Err(e) => println!("Error: {}", e),
     }
+    match process_part_2_pick(INPUT) {
+        Ok(result) => println!("Part 2 result (Pick's theorem)\n\t{}", result),
+        Err(e) => println!("Error: {}", e),
+    }
+}
+
+fn parse_loop(input: &str) -> Result<(Maze, PipeLoop), AoCError> {
+    let maze = Maze::parse(input)?;
+    let pipe_loop = maze.main_loop()?;
+    Ok((maze, pipe_loop))
 }
 
 fn process_part_1(input: &str) -> Result<usize, AoCError> {
-    let maze_data = Maze::new(input);
-    let mut maze_pos_fwd = MazePos::new(&maze_data, MoveDirection::Forward);
-    let mut maze_pos_bwd = maze_pos_fwd;
-    maze_pos_bwd.move_direction = MoveDirection::Backward;
-    let mut result = 0;
-    maze_pos_fwd.next();
-    maze_pos_bwd.next();
-    while !(maze_pos_fwd.pos.0 == maze_pos_bwd.pos.0 && maze_pos_fwd.pos.1 == maze_pos_bwd.pos.1) {
-        maze_pos_fwd.next();
-        if maze_pos_fwd.pos.0 == maze_pos_bwd.pos.0 && maze_pos_fwd.pos.1 == maze_pos_bwd.pos.1 {
-            break;
-        }
-        result = maze_pos_bwd.next();
-    }
-    Ok(result)
+    let (_, pipe_loop) = parse_loop(input)?;
+    Ok(pipe_loop.farthest())
 }
 
 fn get_right_direction(dir: usize) -> usize {
@@ -250,86 +85,66 @@ fn get_left_direction(dir: usize) -> usize {
     dir + 1
 }
 
-fn set_mark(maze: &mut [Vec<char>], dir: usize, mark: char, pos: (i32, i32)) {
+fn direction_between(from: Point, to: Point) -> usize {
+    (0..4)
+        .find(|dir| (from.0 + MOVES[*dir][0], from.1 + MOVES[*dir][1]) == to)
+        .expect("loop tiles follow on from each other")
+}
+
+fn set_mark(maze: &mut [Vec<char>], dir: usize, mark: char, pos: Point) {
     let next_x = pos.0 + MOVES[dir][0];
     let next_y = pos.1 + MOVES[dir][1];
     if next_x >= 0
         && next_y >= 0
-        && next_x < (maze[0].len() as i32)
-        && next_y < (maze.len() as i32)
+        && next_x < (maze[0].len() as i64)
+        && next_y < (maze.len() as i64)
         && maze[next_y as usize][next_x as usize] == '.'
     {
         maze[next_y as usize][next_x as usize] = mark;
     }
 }
 
-fn reconstruct_maze(input: &str) -> Result<Vec<Vec<char>>, AoCError> {
-    let maze = Maze::new(input);
-    let mut cleared_maze = vec![vec!['.'; maze.width]; maze.height];
-    let mut mark = MazePos::new(&maze, MoveDirection::Forward);
-    cleared_maze[mark.start.1 as usize][mark.start.0 as usize] = 'S';
-    mark.next();
-    // Idea:
-    // Traveling CW, the inside is always to the right of the direction we're heading
-    // However, we don't know which direction we're heading when we go around the pipe, even though we do know they are distinct
-    // Let's mark it, and then decide later based on whichever touches the edges
-    while !(mark.pos.0 == mark.start.0 && mark.pos.1 == mark.start.1) {
-        let block = match mark.get() {
-            Some(x) => x,
-            _ => panic!(),
-        };
-        cleared_maze[mark.pos.1 as usize][mark.pos.0 as usize] = block;
-        let left = get_left_direction(mark.prev);
-        let right = get_right_direction(mark.prev);
+// Only the loop's pipes, with the ground on its left marked `X` and on its right `Y`
+fn reconstruct_maze(maze: &Maze, pipe_loop: &PipeLoop) -> Vec<Vec<char>> {
+    let mut cleared_maze = vec![vec!['.'; maze.width()]; maze.height()];
+    for (pos, block) in pipe_loop.pipes(maze) {
+        cleared_maze[pos.1 as usize][pos.0 as usize] = block;
+    }
+    let count = pipe_loop.tiles.len();
+    for (index, (pos, block)) in pipe_loop.pipes(maze).enumerate() {
+        // The side of the tile the walk came in from
+        let prev = direction_between(pos, pipe_loop.tiles[(index + count - 1) % count]);
+        let left = get_left_direction(prev);
+        let right = get_right_direction(prev);
 
         // Set side & outside corners
-        set_mark(&mut cleared_maze, left, 'X', mark.pos);
-        if mark.prev == WEST && block == '7'
-            || mark.prev == NORTH && block == 'J'
-            || mark.prev == EAST && block == 'L'
-            || mark.prev == SOUTH && block == 'F'
+        set_mark(&mut cleared_maze, left, 'X', pos);
+        if prev == WEST && block == '7'
+            || prev == NORTH && block == 'J'
+            || prev == EAST && block == 'L'
+            || prev == SOUTH && block == 'F'
         {
-            set_mark(
-                &mut cleared_maze,
-                opposite_direction(mark.prev),
-                'X',
-                mark.pos,
-            );
+            set_mark(&mut cleared_maze, opposite_direction(prev), 'X', pos);
         }
 
-        set_mark(&mut cleared_maze, right, 'Y', mark.pos);
-        if mark.prev == SOUTH && block == '7'
-            || mark.prev == WEST && block == 'J'
-            || mark.prev == NORTH && block == 'L'
-            || mark.prev == EAST && block == 'F'
+        set_mark(&mut cleared_maze, right, 'Y', pos);
+        if prev == SOUTH && block == '7'
+            || prev == WEST && block == 'J'
+            || prev == NORTH && block == 'L'
+            || prev == EAST && block == 'F'
         {
-            set_mark(
-                &mut cleared_maze,
-                opposite_direction(mark.prev),
-                'Y',
-                mark.pos,
-            );
+            set_mark(&mut cleared_maze, opposite_direction(prev), 'Y', pos);
         }
-        mark.next();
     }
-    Ok(cleared_maze)
+    cleared_maze
 }
 
-fn determine_outside(maze: &[Vec<char>]) -> Option<char> {
-    // This is getting long, so I'm going to do a little shortcut because I can at the cost of rigor.
-    // Normally, you'd scan the entire perimeter and search the open space until you found which
-    // char is the outside, but we can just scan the first line in this case since all of them have
-    // an "outside" that touches the first line.
-    // I'm also not considering the worst case: that the pipe expands the perimeter of the entire grid.
-    // In that case, you'd want the newly constructed grid to be +2 x and y bigger than the original so
-    // that there's empty space to be found around it.
-    let line = maze.iter().next().unwrap();
-    for block in line.iter() {
-        if *block == 'X' || *block == 'Y' {
-            return Some(*block);
-        }
+// With y growing downwards, a clockwise loop has its inside on the right
+fn inside_mark(maze: &Maze, pipe_loop: &PipeLoop) -> char {
+    match loop_polygon(maze, pipe_loop).winding() {
+        Winding::Clockwise => 'Y',
+        _ => 'X',
     }
-    None
 }
 
 fn count_inside(maze: &[Vec<char>], inside: char) -> usize {
@@ -338,7 +153,7 @@ fn count_inside(maze: &[Vec<char>], inside: char) -> usize {
         .sum()
 }
 
-fn fill_inside(maze: &mut Vec<Vec<char>>, inside: char) {
+fn fill_inside(maze: &mut [Vec<char>], inside: char) {
     let mut visited = vec![vec![false; maze[0].len()]; maze.len()];
     let mut q = Queue::new();
     maze.iter().enumerate().for_each(|(row, line)| {
@@ -353,12 +168,12 @@ fn fill_inside(maze: &mut Vec<Vec<char>>, inside: char) {
     while !q.is_empty() {
         let (col, row) = q.dequeue().unwrap();
         for next in MOVES.iter() {
-            let next_row = (row as i32) + next[0];
-            let next_col = (col as i32) + next[1];
+            let next_row = (row as i64) + next[0];
+            let next_col = (col as i64) + next[1];
             if next_row >= 0
                 && next_col >= 0
-                && next_row < (maze.len() as i32)
-                && next_col < (maze[0].len() as i32)
+                && next_row < (maze.len() as i64)
+                && next_col < (maze[0].len() as i64)
                 && maze[next_row as usize][next_col as usize] == '.'
                 && !visited[next_row as usize][next_col as usize]
             {
@@ -370,31 +185,75 @@ fn fill_inside(maze: &mut Vec<Vec<char>>, inside: char) {
     }
 }
 
+// The reconstructed maze with every inside tile marked, and the mark used
+fn fill_maze(maze: &Maze, pipe_loop: &PipeLoop) -> (Vec<Vec<char>>, char) {
+    let mut cleared_maze = reconstruct_maze(maze, pipe_loop);
+    let inside = inside_mark(maze, pipe_loop);
+    fill_inside(&mut cleared_maze, inside);
+    (cleared_maze, inside)
+}
+
 fn process_part_2(input: &str) -> Result<usize, AoCError> {
-    let mut maze = reconstruct_maze(input)?;
+    let (maze, pipe_loop) = parse_loop(input)?;
+    let (cleared_maze, inside) = fill_maze(&maze, &pipe_loop);
+    Ok(count_inside(&cleared_maze, inside))
+}
 
-    let outside = determine_outside(&maze).unwrap();
-    let inside = match outside {
-        'X' => 'Y',
-        'Y' => 'X',
-        _ => panic!(),
-    };
-    println!("Outside is: {}", outside);
-    println!("Inside is: {}", inside);
-    fill_inside(&mut maze, inside);
-    for line in maze.iter() {
-        for point in line.iter() {
-            print!("{}", point);
-        }
-        println!();
+fn tile_map(input: &str) -> Result<TileMap, AoCError> {
+    let (maze, pipe_loop) = parse_loop(input)?;
+    let (cleared_maze, inside) = fill_maze(&maze, &pipe_loop);
+    let tiles = cleared_maze
+        .iter()
+        .enumerate()
+        .map(|(row, line)| {
+            line.iter()
+                .enumerate()
+                .map(|(col, block)| {
+                    let original = maze.get((col as i64, row as i64)).unwrap_or('.');
+                    match block {
+                        '|' | '-' | 'L' | 'J' | '7' | 'F' => Tile::Loop(*block),
+                        block if *block == inside => Tile::Inside(original),
+                        _ => Tile::Outside(original),
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    Ok(tiles)
+}
+
+fn render_part_2(
+    input: &str,
+    render: Option<fn(&TileMap) -> String>,
+    svg_path: Option<String>,
+) -> Result<(), AoCError> {
+    let tiles = tile_map(input)?;
+    if let Some(render) = render {
+        println!("{}\n", render(&tiles));
+    }
+    if let Some(path) = svg_path {
+        let file = File::create(&path).map_err(|e| AoCError::RenderError(e.to_string()))?;
+        write_svg(BufWriter::new(file), &tiles, 12)
+            .map_err(|e| AoCError::RenderError(e.to_string()))?;
     }
-    let count = count_inside(&maze, inside);
-    Ok(count)
+    Ok(())
+}
+
+fn loop_polygon(maze: &Maze, pipe_loop: &PipeLoop) -> Polygon {
+    Polygon::from_vertices(pipe_loop.vertices(maze))
+}
+
+// Same count as part 2, from the loop's area with Pick's theorem instead of a flood fill
+fn process_part_2_pick(input: &str) -> Result<usize, AoCError> {
+    let (maze, pipe_loop) = parse_loop(input)?;
+    let enclosed = loop_polygon(&maze, &pipe_loop).interior_points();
+    Ok(enclosed as usize)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    const INPUT: &str = include_str!("./input.txt");
     #[test]
     fn part_1() -> Result<()> {
         let input_1 = ".....
@@ -446,7 +305,148 @@ L7JLJL-JLJLJL--JLJ.L";
         assert_eq!(process_part_2(input_1)?, 4);
         assert_eq!(process_part_2(input_2)?, 8);
         assert_eq!(process_part_2(input_3)?, 10);
+        for input in [input_1, input_2, input_3] {
+            assert_eq!(process_part_2_pick(input)?, process_part_2(input)?);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn rendering() -> Result<()> {
+        let input = "..........
+.S------7.
+.|F----7|.
+.||....||.
+.||....||.
+.|L-7F-J|.
+.|..||..|.
+.L--JL--J.
+..........";
+        let tiles = tile_map(input)?;
+        assert_eq!(
+            plain_map(&tiles),
+            "OOOOOOOOOO
+O┌──────┐O
+O│┌────┐│O
+O││OOOO││O
+O││OOOO││O
+O│└─┐┌─┘│O
+O│II││II│O
+O└──┘└──┘O
+OOOOOOOOOO"
+        );
+        assert_eq!(day_10::count_inside(&tiles), process_part_2(input)?);
+
+        // Junk pipes keep their shape wherever they are
+        let input = "7-F7-
+.FJ|7
+SJLL7
+|F--J
+LJ.LJ";
+        let tiles = tile_map(input)?;
+        assert_eq!(tiles[0][0], Tile::Outside('7'));
+        assert_eq!(tiles[2][0], Tile::Loop('F'));
+        assert_eq!(tiles[2][2], Tile::Inside('L'));
+        let ansi = ansi_map(&tiles);
+        assert!(ansi.contains("\x1b[2;30;42m└"));
+        assert!(ansi.starts_with("\x1b[90;44m┐"));
+        assert!(!ansi.contains("\x1b[30;42m└"));
+        let svg = day_10::render::svg_map(&tiles, 10);
+        assert!(svg.starts_with("<svg") && svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("#2e7d32").count(), 1);
+        Ok(())
+    }
 
+    #[test]
+    fn walker() -> Result<()> {
+        let input = "7-F7-
+.FJ|7
+SJLL7
+|F--J
+LJ.LJ";
+        let maze = Maze::parse(input)?;
+        let pipe_loop = maze.main_loop()?;
+        assert_eq!(pipe_loop.start_pipe, 'F');
+        assert_eq!(pipe_loop.tiles.len(), 16);
+        assert_eq!(pipe_loop.tiles[0], (0, 2));
+        assert_eq!(
+            pipe_loop.vertices(&maze),
+            [
+                (0, 2),
+                (1, 2),
+                (1, 1),
+                (2, 1),
+                (2, 0),
+                (3, 0),
+                (3, 2),
+                (4, 2),
+                (4, 3),
+                (1, 3),
+                (1, 4),
+                (0, 4)
+            ]
+        );
+
+        // Junk pipes pointing into `S` as well as the two loop pipes
+        let input = ".7...
+-S-7.
+.|.|.
+.L-J.
+.....";
+        assert_eq!(Maze::parse(input)?.main_loop()?.start_pipe, 'F');
+        assert_eq!(process_part_1(input)?, 4);
+
+        let errors = [
+            ("", MazeError::Empty),
+            (
+                ".|.\n.L-7",
+                MazeError::Ragged {
+                    row: 1,
+                    width: 4,
+                    expected: 3,
+                },
+            ),
+            (
+                "..\n.x",
+                MazeError::UnknownTile {
+                    tile: 'x',
+                    at: (1, 1),
+                },
+            ),
+            ("F7\nLJ", MazeError::NoStart),
+            ("S7\nLS", MazeError::ManyStarts((0, 0), (1, 1))),
+            ("S-.\n...", MazeError::DeadEnd(1)),
+            (
+                "S7.\n|L.",
+                MazeError::Disconnected {
+                    pipe: '.',
+                    at: (2, 1),
+                },
+            ),
+            ("S-\n|.", MazeError::OffEdge((2, 0))),
+        ];
+        for (input, error) in errors {
+            assert_eq!(
+                Maze::parse(input).and_then(|maze| maze.main_loop()).err(),
+                Some(error)
+            );
+        }
+        assert!(matches!(
+            process_part_2("S7\nLS"),
+            Err(AoCError::MazeError(MazeError::ManyStarts(..)))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn pick_agrees_with_flood_fill() -> Result<()> {
+        // Loops running along the edge of the grid, both ways round
+        for input in ["S-7\n|.|\nL-J", "F-7\n|.|\nS-J", "F7..\n|L-7\nS--J"] {
+            assert_eq!(process_part_2_pick(input)?, process_part_2(input)?);
+        }
+        assert_eq!(process_part_2("S-7\n|.|\nL-J")?, 1);
+        assert_eq!(process_part_2(INPUT)?, process_part_2_pick(INPUT)?);
         Ok(())
     }
 }
@@ -0,0 +1,147 @@
+use crate::{Tile, TileMap};
+use std::io::Write;
+
+const ANSI_RESET: &str = "\x1b[0m";
+const ANSI_LOOP: &str = "\x1b[1;37m";
+const ANSI_INSIDE: &str = "\x1b[30;42m";
+const ANSI_OUTSIDE: &str = "\x1b[2;37;44m";
+const ANSI_INSIDE_JUNK: &str = "\x1b[2;30;42m";
+const ANSI_OUTSIDE_JUNK: &str = "\x1b[90;44m";
+
+const SVG_BACKGROUND: &str = "#0f0f23";
+const SVG_INSIDE: &str = "#2e7d32";
+const SVG_OUTSIDE: &str = "#1a2a4a";
+const SVG_LOOP: &str = "#ffcc00";
+const SVG_JUNK: &str = "#50505a";
+
+// The edges each pipe reaches from the middle of its tile, as (dx, dy)
+fn arms(pipe: char) -> &'static [(i32, i32)] {
+    match pipe {
+        '|' => &[(0, -1), (0, 1)],
+        '-' => &[(-1, 0), (1, 0)],
+        'L' => &[(0, -1), (1, 0)],
+        'J' => &[(0, -1), (-1, 0)],
+        '7' => &[(0, 1), (-1, 0)],
+        'F' => &[(0, 1), (1, 0)],
+        _ => &[],
+    }
+}
+
+pub fn box_glyph(pipe: char) -> char {
+    match pipe {
+        '|' => '│',
+        '-' => '─',
+        'L' => '└',
+        'J' => '┘',
+        '7' => '┐',
+        'F' => '┌',
+        _ => pipe,
+    }
+}
+
+// Box drawing for the loop and the puzzle's `I` and `O` for everything else, junk included
+pub fn plain_map(tiles: &TileMap) -> String {
+    tiles
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|tile| match tile {
+                    Tile::Loop(pipe) => box_glyph(*pipe),
+                    Tile::Inside(_) => 'I',
+                    Tile::Outside(_) => 'O',
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Junk pipes keep their shape but are greyed out on the colour of the side they're on
+pub fn ansi_map(tiles: &TileMap) -> String {
+    let mut result = String::new();
+    for row in tiles {
+        for tile in row {
+            let (colour, glyph) = match tile {
+                Tile::Loop(pipe) => (ANSI_LOOP, box_glyph(*pipe)),
+                Tile::Inside('.') => (ANSI_INSIDE, ' '),
+                Tile::Outside('.') => (ANSI_OUTSIDE, ' '),
+                Tile::Inside(pipe) => (ANSI_INSIDE_JUNK, box_glyph(*pipe)),
+                Tile::Outside(pipe) => (ANSI_OUTSIDE_JUNK, box_glyph(*pipe)),
+            };
+            result.push_str(colour);
+            result.push(glyph);
+        }
+        result.push_str(ANSI_RESET);
+        result.push('\n');
+    }
+    result
+}
+
+fn svg_pipe(
+    out: &mut String,
+    (col, row): (usize, usize),
+    scale: usize,
+    pipe: char,
+    (colour, width): (&str, usize),
+) {
+    let half = scale as i32 / 2;
+    let (x, y) = ((col * scale) as i32 + half, (row * scale) as i32 + half);
+    let path: String = arms(pipe)
+        .iter()
+        .map(|(dx, dy)| format!("M{} {}L{} {}", x, y, x + dx * half, y + dy * half))
+        .collect();
+    if !path.is_empty() {
+        out.push_str(&format!(
+            "<path d=\"{}\" stroke=\"{}\" stroke-width=\"{}\" stroke-linecap=\"round\"/>\n",
+            path,
+            colour,
+            width.max(1)
+        ));
+    }
+}
+
+// `scale` is the side of a tile in pixels
+pub fn svg_map(tiles: &TileMap, scale: usize) -> String {
+    let height = tiles.len() * scale;
+    let width = tiles.first().map_or(0, |row| row.len()) * scale;
+    let mut result = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n",
+        width, height
+    );
+    result.push_str(&format!(
+        "<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+        width, height, SVG_BACKGROUND
+    ));
+    for (row, line) in tiles.iter().enumerate() {
+        for (col, tile) in line.iter().enumerate() {
+            let fill = match tile {
+                Tile::Loop(_) => continue,
+                Tile::Inside(_) => SVG_INSIDE,
+                Tile::Outside(_) => SVG_OUTSIDE,
+            };
+            result.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+                col * scale,
+                row * scale,
+                scale,
+                scale,
+                fill
+            ));
+        }
+    }
+    for (row, line) in tiles.iter().enumerate() {
+        for (col, tile) in line.iter().enumerate() {
+            let (pipe, stroke) = match tile {
+                Tile::Loop(pipe) => (*pipe, (SVG_LOOP, scale / 4)),
+                Tile::Inside(pipe) | Tile::Outside(pipe) => (*pipe, (SVG_JUNK, scale / 8)),
+            };
+            svg_pipe(&mut result, (col, row), scale, pipe, stroke);
+        }
+    }
+    result.push_str("</svg>\n");
+    result
+}
+
+pub fn write_svg(mut out: impl Write, tiles: &TileMap, scale: usize) -> std::io::Result<()> {
+    out.write_all(svg_map(tiles, scale).as_bytes())
+}
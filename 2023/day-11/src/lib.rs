@@ -0,0 +1,142 @@
+// Positions are (x, y) after expansion
+pub type Point = (i64, i64);
+
+#[derive(Debug, Clone)]
+pub struct Universe {
+    galaxies: Vec<(usize, usize)>,
+    // empty_cols[x] is how many empty columns come before column x, likewise for rows
+    empty_cols: Vec<usize>,
+    empty_rows: Vec<usize>,
+}
+
+fn count_before(occupied: &[bool]) -> Vec<usize> {
+    let mut result = Vec::with_capacity(occupied.len());
+    let mut empty = 0;
+    for occupied in occupied {
+        result.push(empty);
+        if !occupied {
+            empty += 1;
+        }
+    }
+    result
+}
+
+impl Universe {
+    pub fn parse(input: &str) -> Result<Universe, String> {
+        let lines: Vec<&str> = input
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+        let width = lines.first().map_or(0, |line| line.len());
+        let mut galaxies = Vec::new();
+        let mut cols = vec![false; width];
+        let mut rows = vec![false; lines.len()];
+        for (y, line) in lines.iter().enumerate() {
+            if line.len() != width {
+                return Err(line.to_string());
+            }
+            for (x, c) in line.bytes().enumerate() {
+                match c {
+                    b'#' => {
+                        galaxies.push((x, y));
+                        cols[x] = true;
+                        rows[y] = true;
+                    }
+                    b'.' => (),
+                    _ => return Err(line.to_string()),
+                }
+            }
+        }
+        Ok(Universe {
+            galaxies,
+            empty_cols: count_before(&cols),
+            empty_rows: count_before(&rows),
+        })
+    }
+
+    // Every empty row and column becomes `factor` of them
+    pub fn expanded(&self, factor: i64) -> Vec<Point> {
+        let grow = |position: usize, empty: usize| position as i64 + empty as i64 * (factor - 1);
+        self.galaxies
+            .iter()
+            .map(|(x, y)| (grow(*x, self.empty_cols[*x]), grow(*y, self.empty_rows[*y])))
+            .collect()
+    }
+}
+
+pub fn manhattan(a: Point, b: Point) -> i64 {
+    (a.0 - b.0).abs() + (a.1 - b.1).abs()
+}
+
+// Sorted, the coordinate at index i is at least every one before it, so it adds
+// i * coord - (the sum of those before) to the total
+fn axis_distance_sum(mut coords: Vec<i64>) -> i64 {
+    coords.sort_unstable();
+    let mut before = 0;
+    let mut total = 0;
+    for (index, coord) in coords.iter().enumerate() {
+        total += index as i64 * coord - before;
+        before += coord;
+    }
+    total
+}
+
+// Manhattan distance splits by axis, so each axis is summed on its own
+pub fn pairwise_distance_sum(points: &[Point]) -> i64 {
+    axis_distance_sum(points.iter().map(|point| point.0).collect())
+        + axis_distance_sum(points.iter().map(|point| point.1).collect())
+}
+
+// For every point, the index of its nearest other point and the distance to it.
+// Sweeps outwards in x order, stopping once the x gap alone is no shorter than the best.
+pub fn nearest_neighbours(points: &[Point]) -> Vec<Option<(usize, i64)>> {
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    order.sort_unstable_by_key(|index| points[*index]);
+    let mut result = vec![None; points.len()];
+    for (rank, index) in order.iter().enumerate() {
+        let point = points[*index];
+        let mut best: Option<(usize, i64)> = None;
+        // False once nothing further along this side can be closer
+        let mut consider = |other: usize| {
+            let gap = (points[other].0 - point.0).abs();
+            if best.is_some_and(|(_, distance)| gap >= distance) {
+                return false;
+            }
+            let distance = manhattan(point, points[other]);
+            if best.is_none_or(|(_, best_distance)| distance < best_distance) {
+                best = Some((other, distance));
+            }
+            true
+        };
+        for other in order[..rank].iter().rev() {
+            if !consider(*other) {
+                break;
+            }
+        }
+        for other in order[rank + 1..].iter() {
+            if !consider(*other) {
+                break;
+            }
+        }
+        result[*index] = best;
+    }
+    result
+}
+
+// In rotated coordinates, x + y and x - y, the Manhattan distance is the larger difference of
+// the two, so the farthest pair is at the ends of one of them
+pub fn farthest_pair(points: &[Point]) -> Option<(usize, usize, i64)> {
+    if points.len() < 2 {
+        return None;
+    }
+    let rotations: [fn(&Point) -> i64; 2] = [|p| p.0 + p.1, |p| p.0 - p.1];
+    rotations
+        .iter()
+        .filter_map(|rotate| {
+            let min = (0..points.len()).min_by_key(|index| rotate(&points[*index]))?;
+            let max = (0..points.len()).max_by_key(|index| rotate(&points[*index]))?;
+            Some((min, max, manhattan(points[min], points[max])))
+        })
+        .max_by_key(|pair| pair.2)
+}
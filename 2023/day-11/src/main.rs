@@ -1,4 +1,5 @@
 use anyhow::Result;
+use day_11::{farthest_pair, nearest_neighbours, pairwise_distance_sum, Universe};
 use thiserror::Error;
 
 const AOC_DAY: &str = "11";
@@ -11,32 +12,6 @@ pub enum AoCError {
     Unknown,
 }
 
-#[derive(Copy, Clone)]
-struct Grid<'a> {
-    data: &'a [u8],
-    width: usize,
-    height: usize,
-}
-
-impl Grid<'_> {
-    pub fn new(data: &str) -> Grid {
-        let height = data.lines().count();
-        let width = data.lines().next().unwrap().chars().count();
-        Grid {
-            data: data.as_bytes(),
-            width,
-            height,
-        }
-    }
-    pub fn get(&self, x: i32, y: i32) -> Option<char> {
-        if x >= (self.width as i32) || y >= (self.height as i32) || x < 0 || y < 0 {
-            return None;
-        }
-        let index = ((y as usize) * (self.width + 1)) + (x as usize);
-        Some(self.data[index] as char)
-    }
-}
-
 fn main() {
     const INPUT: &str = include_str!("./input.txt");
     println!(
@@ -51,106 +26,54 @@ fn main() {
         Ok(result) => println!("Part 2 result\n\t{}", result),
         Err(e) => println!("Error: {}", e),
     }
-}
-
-fn get_zeros(grid: &Grid) -> Result<(Vec<usize>, Vec<usize>), AoCError> {
-    let mut row_count = vec![0; grid.height];
-    let mut col_count = vec![0; grid.width];
-    row_count
-        .iter_mut()
-        .enumerate()
-        .for_each(|(row_index, row)| {
-            col_count
-                .iter_mut()
-                .enumerate()
-                .for_each(|(col_index, col)| {
-                    let block = match grid.get(col_index as i32, row_index as i32) {
-                        Some(x) => x,
-                        _ => panic!("invalid char in the grid!"),
-                    };
-                    if block == '#' {
-                        *row += 1;
-                        *col += 1;
-                    }
-                });
-        });
-    Ok((row_count, col_count))
-}
-
-// fn reconstruct_grid(grid: &Grid, row_count: &[usize], col_count: &[usize]) -> Vec<Vec<char>> {
-//     let mut expanded_grid: Vec<Vec<char>> = Vec::new();
-//     row_count.iter().enumerate().for_each(|(row, &row_num)| {
-//         let mut next_row: Vec<char> = Vec::new();
-//         col_count.iter().enumerate().for_each(|(col, &col_num)| {
-//             next_row.push(grid.get(col as i32, row as i32).unwrap());
-//             if col_num == 0 {
-//                 next_row.push('.');
-//             }
-//         });
-//         if row_num == 0 {
-//             expanded_grid.push(next_row.clone());
-//         }
-//         expanded_grid.push(next_row);
-//     });
-//     expanded_grid
-// }
-
-fn get_points(grid: Grid) -> Vec<(usize, usize)> {
-    let mut points: Vec<(usize, usize)> = Vec::new();
-    for row_index in 0..grid.height {
-        for col_index in 0..grid.width {
-            if grid.get(col_index as i32, row_index as i32).unwrap() == '#' {
-                points.push((col_index, row_index));
-            }
+    // Usage: day-11 [--queries] [--expansion <factor>]
+    let mut queries = false;
+    let mut expansion = 2;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--queries" => queries = true,
+            "--expansion" => expansion = args.next().and_then(|x| x.parse().ok()).unwrap_or(2),
+            _ => println!("Ignoring unknown argument `{}`", arg),
         }
     }
-    points
-}
-
-fn manhattan_dist(a: (usize, usize), b: (usize, usize)) -> i64 {
-    (a.0 as i64 - b.0 as i64).abs() + (a.1 as i64 - b.1 as i64).abs()
-}
-
-fn get_num_empty(row_count: &[usize], col_count: &[usize], a: (usize, usize), b: (usize, usize)) -> i64 {
-    let left_col = a.0.min(b.0);
-    let right_col = a.0.max(b.0);
-    let top_row = a.1.min(b.1);
-    let bot_row = a.1.max(b.1);
-    let mut count = 0;
-    for index in left_col..right_col {
-        let cnt = match col_count.get(index) {
-            Some(x) => *x,
-            _=> panic!("Index out of range"),
-        };
-        if cnt == 0 {
-            count += 1;
+    if queries {
+        if let Err(e) = print_queries(INPUT, expansion) {
+            println!("Error: {}", e);
         }
     }
-    for index in top_row..bot_row {
-        let cnt = match row_count.get(index) {
-            Some(x) => *x,
-            _=> panic!("Index out of range"),
-        };
-        if cnt == 0 {
-            count += 1;
-        }
-    }
-    count
 }
 
 fn process_part_1(input: &str, expansion: i64) -> Result<i64, AoCError> {
-    let grid = Grid::new(input);
-    let (row_count, col_count) = get_zeros(&grid)?;
-    let points = get_points(grid);
-    let mut result = 0;
+    let universe = Universe::parse(input).map_err(AoCError::ParsingError)?;
+    Ok(pairwise_distance_sum(&universe.expanded(expansion)))
+}
 
-    for i in 0..points.len() {
-        for x in i + 1..points.len() {
-            result += manhattan_dist(points[i], points[x]);
-            result += get_num_empty(&row_count, &col_count, points[i], points[x]) * (expansion - 1);
+fn print_queries(input: &str, expansion: i64) -> Result<(), AoCError> {
+    let galaxies = Universe::parse(input)
+        .map_err(AoCError::ParsingError)?
+        .expanded(expansion);
+    for (index, nearest) in nearest_neighbours(&galaxies).iter().enumerate() {
+        if let Some((other, distance)) = nearest {
+            println!(
+                "Galaxy {} at {:?}: nearest is galaxy {} at {:?}, {} away",
+                index + 1,
+                galaxies[index],
+                other + 1,
+                galaxies[*other],
+                distance
+            );
         }
     }
-    Ok(result)
+    if let Some((a, b, distance)) = farthest_pair(&galaxies) {
+        println!(
+            "Farthest pair: galaxies {} and {}, {} apart",
+            a + 1,
+            b + 1,
+            distance
+        );
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -174,4 +97,45 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn queries() -> Result<()> {
+        let input = "...#......
+.......#..
+#.........
+..........
+......#...
+.#........
+.........#
+..........
+.......#..
+#...#.....";
+        let galaxies = Universe::parse(input).unwrap().expanded(2);
+        assert_eq!(galaxies[0], (4, 0));
+        assert_eq!(galaxies[8], (5, 11));
+        // Galaxy 5 to 9 is 9 apart, as in the puzzle
+        assert_eq!(day_11::manhattan(galaxies[4], galaxies[8]), 9);
+        let brute: i64 = (0..galaxies.len())
+            .flat_map(|a| (a + 1..galaxies.len()).map(move |b| (a, b)))
+            .map(|(a, b)| day_11::manhattan(galaxies[a], galaxies[b]))
+            .sum();
+        assert_eq!(brute, 374);
+
+        let nearest = nearest_neighbours(&galaxies);
+        for (index, found) in nearest.iter().enumerate() {
+            let best = (0..galaxies.len())
+                .filter(|other| *other != index)
+                .map(|other| day_11::manhattan(galaxies[index], galaxies[other]))
+                .min();
+            assert_eq!(found.map(|(_, distance)| distance), best);
+        }
+        let farthest = (0..galaxies.len())
+            .flat_map(|a| (a + 1..galaxies.len()).map(move |b| (a, b)))
+            .map(|(a, b)| day_11::manhattan(galaxies[a], galaxies[b]))
+            .max();
+        assert_eq!(farthest_pair(&galaxies).map(|pair| pair.2), farthest);
+        assert_eq!(farthest_pair(&galaxies[..1]), None);
+        assert!(Universe::parse("..#\n.#").is_err());
+        assert!(Universe::parse("..#\n.x.").is_err());
+        Ok(())
+    }
 }
@@ -0,0 +1,408 @@
+use num::{BigUint, One, Zero};
+
+pub const FILLED: char = '#';
+pub const EMPTY: char = '.';
+pub const UNKNOWN: char = '?';
+
+// Indexed by row then column
+pub type Grid = Vec<Vec<char>>;
+
+// One row of cells against the lengths of the runs of filled cells in it, in order.
+//
+// Every arrangement is a path through states (i, j): the first i cells are settled and the
+// first j runs placed. From (i, j) cell i can be left empty, going to (i + 1, j), or run j can
+// start there, going past the run and the empty cell after it to (i + len + 1, j + 1).
+#[derive(Debug, Clone, Copy)]
+pub struct Line<'a> {
+    cells: &'a [char],
+    runs: &'a [usize],
+}
+
+impl<'a> Line<'a> {
+    pub fn new(cells: &'a [char], runs: &'a [usize]) -> Line<'a> {
+        Line { cells, runs }
+    }
+
+    // Whether a run of `len` can start at `start`, with nothing filled straight after it
+    fn fits(&self, start: usize, len: usize) -> bool {
+        let end = start + len;
+        end <= self.cells.len()
+            && self.cells[start..end].iter().all(|cell| *cell != EMPTY)
+            && self.cells.get(end).is_none_or(|cell| *cell != FILLED)
+    }
+
+    fn after_run(&self, start: usize, len: usize) -> usize {
+        (start + len + 1).min(self.cells.len())
+    }
+
+    // suffix[i][j] sums over the ways to finish from (i, j)
+    fn suffix<T: Clone>(&self, zero: T, one: T, add: impl Fn(T, &T) -> T) -> Vec<Vec<T>> {
+        let (width, count) = (self.cells.len(), self.runs.len());
+        let mut suffix = vec![vec![zero.clone(); count + 1]; width + 1];
+        suffix[width][count] = one;
+        for i in (0..width).rev() {
+            for j in 0..=count {
+                let mut total = zero.clone();
+                if self.cells[i] != FILLED {
+                    total = add(total, &suffix[i + 1][j]);
+                }
+                if j < count && self.fits(i, self.runs[j]) {
+                    total = add(total, &suffix[self.after_run(i, self.runs[j])][j + 1]);
+                }
+                suffix[i][j] = total;
+            }
+        }
+        suffix
+    }
+
+    // Which states can be reached from the start
+    fn prefix(&self) -> Vec<Vec<bool>> {
+        let (width, count) = (self.cells.len(), self.runs.len());
+        let mut prefix = vec![vec![false; count + 1]; width + 1];
+        prefix[0][0] = true;
+        for i in 0..width {
+            for j in 0..=count {
+                if !prefix[i][j] {
+                    continue;
+                }
+                if self.cells[i] != FILLED {
+                    prefix[i + 1][j] = true;
+                }
+                if j < count && self.fits(i, self.runs[j]) {
+                    prefix[self.after_run(i, self.runs[j])][j + 1] = true;
+                }
+            }
+        }
+        prefix
+    }
+
+    // In whatever integer type is big enough, BigUint when nothing else is
+    pub fn count<T: Clone + Zero + One>(&self) -> T {
+        self.suffix(T::zero(), T::one(), |a, b| a + b.clone())[0][0].clone()
+    }
+
+    // Every arrangement, lazily, with empty cells tried before runs
+    pub fn arrangements(&self) -> Arrangements<'a> {
+        let alive = self.suffix(false, true, |a, b| a || *b);
+        let stack = match alive[0][0] {
+            true => vec![(0, 0, Vec::with_capacity(self.cells.len()))],
+            false => Vec::new(),
+        };
+        Arrangements {
+            line: *self,
+            alive,
+            stack,
+        }
+    }
+
+    pub fn sampler(&self) -> Sampler<'a> {
+        Sampler {
+            line: *self,
+            counts: self.suffix(BigUint::zero(), BigUint::one(), |a, b| a + b),
+        }
+    }
+
+    // The cells with every one that's the same in all arrangements filled in, or None when
+    // there are no arrangements at all
+    pub fn deduce(&self) -> Option<Vec<char>> {
+        let (width, count) = (self.cells.len(), self.runs.len());
+        let suffix = self.suffix(false, true, |a, b| a || *b);
+        if !suffix[0][0] {
+            return None;
+        }
+        let prefix = self.prefix();
+        let mut can_fill = vec![false; width];
+        let mut can_empty = vec![false; width];
+        for i in 0..width {
+            for j in 0..=count {
+                if !prefix[i][j] {
+                    continue;
+                }
+                if self.cells[i] != FILLED && suffix[i + 1][j] {
+                    can_empty[i] = true;
+                }
+                if j < count && self.fits(i, self.runs[j]) {
+                    let len = self.runs[j];
+                    if suffix[self.after_run(i, len)][j + 1] {
+                        can_fill[i..i + len]
+                            .iter_mut()
+                            .for_each(|cell| *cell = true);
+                        if i + len < width {
+                            can_empty[i + len] = true;
+                        }
+                    }
+                }
+            }
+        }
+        let deduced = (0..width)
+            .map(|i| match (can_fill[i], can_empty[i]) {
+                (true, false) => FILLED,
+                (false, true) => EMPTY,
+                _ => self.cells[i],
+            })
+            .collect();
+        Some(deduced)
+    }
+}
+
+// Depth first through the states, only ever stepping into ones that can still finish, so each
+// arrangement takes time linear in the width to reach
+pub struct Arrangements<'a> {
+    line: Line<'a>,
+    alive: Vec<Vec<bool>>,
+    stack: Vec<(usize, usize, Vec<char>)>,
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = Vec<char>;
+
+    fn next(&mut self) -> Option<Vec<char>> {
+        let (cells, runs) = (self.line.cells, self.line.runs);
+        while let Some((i, j, arranged)) = self.stack.pop() {
+            if i == cells.len() {
+                return Some(arranged);
+            }
+            // Pushed in reverse, so leaving the cell empty comes off the stack first
+            if j < runs.len() && self.line.fits(i, runs[j]) {
+                let after = self.line.after_run(i, runs[j]);
+                if self.alive[after][j + 1] {
+                    let mut next = arranged.clone();
+                    next.extend(std::iter::repeat_n(FILLED, runs[j]));
+                    next.resize(after, EMPTY);
+                    self.stack.push((after, j + 1, next));
+                }
+            }
+            if cells[i] != FILLED && self.alive[i + 1][j] {
+                let mut next = arranged;
+                next.push(EMPTY);
+                self.stack.push((i + 1, j, next));
+            }
+        }
+        None
+    }
+}
+
+// SplitMix64, enough to pick arrangements without pulling in a crate for it
+#[derive(Debug, Clone)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Random {
+        Random { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    // Uniform in [0, bound), by drawing as many bits as the bound has until one lands below it
+    pub fn below(&mut self, bound: &BigUint) -> BigUint {
+        let bits = bound.bits();
+        loop {
+            let digits: Vec<u64> = (0..bits.div_ceil(64)).map(|_| self.next_u64()).collect();
+            let mut value = BigUint::from_slice(
+                &digits
+                    .iter()
+                    .flat_map(|digit| [*digit as u32, (*digit >> 32) as u32])
+                    .collect::<Vec<u32>>(),
+            );
+            value &= (BigUint::one() << bits) - BigUint::one();
+            if &value < bound {
+                return value;
+            }
+        }
+    }
+}
+
+// Draws arrangements uniformly: at every state each way on is taken in proportion to how many
+// arrangements finish from there
+pub struct Sampler<'a> {
+    line: Line<'a>,
+    counts: Vec<Vec<BigUint>>,
+}
+
+impl Sampler<'_> {
+    pub fn count(&self) -> &BigUint {
+        &self.counts[0][0]
+    }
+
+    // None when there's nothing to draw from
+    pub fn sample(&self, random: &mut Random) -> Option<Vec<char>> {
+        if self.count().is_zero() {
+            return None;
+        }
+        let (cells, runs) = (self.line.cells, self.line.runs);
+        let mut arranged = Vec::with_capacity(cells.len());
+        let (mut i, mut j) = (0, 0);
+        while i < cells.len() {
+            let empty = match cells[i] != FILLED {
+                true => self.counts[i + 1][j].clone(),
+                false => BigUint::zero(),
+            };
+            let run = match j < runs.len() && self.line.fits(i, runs[j]) {
+                true => self.counts[self.line.after_run(i, runs[j])][j + 1].clone(),
+                false => BigUint::zero(),
+            };
+            if random.below(&(&empty + run)) < empty {
+                arranged.push(EMPTY);
+                i += 1;
+            } else {
+                let after = self.line.after_run(i, runs[j]);
+                arranged.extend(std::iter::repeat_n(FILLED, runs[j]));
+                arranged.resize(after, EMPTY);
+                (i, j) = (after, j + 1);
+            }
+        }
+        Some(arranged)
+    }
+}
+
+// `factor` copies of the cells joined by `separator`, and as many copies of the runs
+pub fn unfold(
+    cells: &[char],
+    runs: &[usize],
+    factor: usize,
+    separator: &str,
+) -> (Vec<char>, Vec<usize>) {
+    let copies = vec![String::from_iter(cells); factor];
+    (
+        copies.join(separator).chars().collect(),
+        runs.repeat(factor),
+    )
+}
+
+// Run lengths separated by commas or spaces, with an empty list written as a lone `0` or
+// nothing. A zero anywhere else would be a run of no cells, so it's refused.
+pub fn parse_runs(text: &str) -> Result<Vec<usize>, String> {
+    if text.trim() == "0" {
+        return Ok(Vec::new());
+    }
+    text.split([',', ' '])
+        .filter(|run| !run.trim().is_empty())
+        .map(|run| match run.trim().parse::<usize>() {
+            Ok(0) => Err(format!("`{}` has a run of length 0", text)),
+            Ok(length) => Ok(length),
+            Err(_) => Err(format!("`{}` isn't a run length", run)),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Solution {
+    None,
+    Unique(Grid),
+    // Two of the solutions, there may be more
+    Multiple(Grid, Grid),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Nonogram {
+    rows: Vec<Vec<usize>>,
+    cols: Vec<Vec<usize>>,
+}
+
+impl Nonogram {
+    pub fn new(rows: Vec<Vec<usize>>, cols: Vec<Vec<usize>>) -> Nonogram {
+        Nonogram { rows, cols }
+    }
+
+    // One line of runs per row, and one per column
+    pub fn parse(rows: &str, cols: &str) -> Result<Nonogram, String> {
+        let parse = |text: &str| {
+            text.lines()
+                .map(parse_runs)
+                .collect::<Result<Vec<_>, String>>()
+        };
+        Ok(Nonogram::new(parse(rows)?, parse(cols)?))
+    }
+
+    // Deduces row by row, then column by column, until nothing changes. False when some row or
+    // column can't be arranged any more.
+    fn propagate(&self, grid: &mut Grid) -> bool {
+        let width = self.cols.len();
+        let mut dirty_rows = vec![true; self.rows.len()];
+        let mut dirty_cols = vec![true; width];
+        while dirty_rows
+            .iter()
+            .chain(dirty_cols.iter())
+            .any(|dirty| *dirty)
+        {
+            for (row, runs) in self.rows.iter().enumerate() {
+                if !std::mem::take(&mut dirty_rows[row]) {
+                    continue;
+                }
+                let Some(deduced) = Line::new(&grid[row], runs).deduce() else {
+                    return false;
+                };
+                for (col, cell) in deduced.into_iter().enumerate() {
+                    if grid[row][col] != cell {
+                        grid[row][col] = cell;
+                        dirty_cols[col] = true;
+                    }
+                }
+            }
+            for (col, runs) in self.cols.iter().enumerate() {
+                if !std::mem::take(&mut dirty_cols[col]) {
+                    continue;
+                }
+                let column: Vec<char> = grid.iter().map(|line| line[col]).collect();
+                let Some(deduced) = Line::new(&column, runs).deduce() else {
+                    return false;
+                };
+                for (row, cell) in deduced.into_iter().enumerate() {
+                    if grid[row][col] != cell {
+                        grid[row][col] = cell;
+                        dirty_rows[row] = true;
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    // Propagates, then guesses the first unknown cell both ways, stopping at `limit` solutions
+    fn search(&self, mut grid: Grid, limit: usize, solutions: &mut Vec<Grid>) {
+        if solutions.len() >= limit || !self.propagate(&mut grid) {
+            return;
+        }
+        let unknown = grid.iter().enumerate().find_map(|(row, line)| {
+            line.iter()
+                .position(|cell| *cell == UNKNOWN)
+                .map(|col| (row, col))
+        });
+        let Some((row, col)) = unknown else {
+            solutions.push(grid);
+            return;
+        };
+        for guess in [FILLED, EMPTY] {
+            let mut next = grid.clone();
+            next[row][col] = guess;
+            self.search(next, limit, solutions);
+        }
+    }
+
+    pub fn solve(&self) -> Solution {
+        let grid = vec![vec![UNKNOWN; self.cols.len()]; self.rows.len()];
+        let mut solutions = Vec::new();
+        self.search(grid, 2, &mut solutions);
+        let mut solutions = solutions.into_iter();
+        match (solutions.next(), solutions.next()) {
+            (Some(first), Some(second)) => Solution::Multiple(first, second),
+            (Some(only), None) => Solution::Unique(only),
+            _ => Solution::None,
+        }
+    }
+}
+
+pub fn format_grid(grid: &Grid) -> String {
+    grid.iter()
+        .map(|line| line.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -1,4 +1,6 @@
 use anyhow::Result;
+use day_12::{format_grid, parse_runs, unfold, Line, Nonogram, Random, Solution};
+use num::{BigUint, Zero};
 use thiserror::Error;
 
 const AOC_DAY: &str = "11";
@@ -17,125 +19,190 @@ fn main() {
         "\n🎄🎄🎄🎄🎄 Advent of Code ||| Day {} 🎄🎄🎄🎄🎄\n",
         AOC_DAY
     );
+    // Usage: day-12 [--nonogram <row clues> <column clues>] [--unfold <factor>] [--separator <text>]
+    //               [--line <n> [--list <limit>] [--sample <count>] [--seed <seed>]]
+    let mut factor = 5;
+    let mut separator = "?".to_string();
+    let mut line_number = None;
+    let (mut list, mut sample, mut seed) = (10, 0, 2023);
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--nonogram" => match (args.next(), args.next()) {
+                (Some(rows), Some(cols)) => {
+                    if let Err(e) = solve_nonogram(&rows, &cols) {
+                        println!("Error: {}", e);
+                    }
+                }
+                _ => println!("`--nonogram` needs a row clue file and a column clue file"),
+            },
+            "--unfold" => factor = args.next().and_then(|x| x.parse().ok()).unwrap_or(5),
+            "--separator" => separator = args.next().unwrap_or_default(),
+            "--line" => line_number = args.next().and_then(|x| x.parse().ok()),
+            "--list" => list = args.next().and_then(|x| x.parse().ok()).unwrap_or(10),
+            "--sample" => sample = args.next().and_then(|x| x.parse().ok()).unwrap_or(0),
+            "--seed" => seed = args.next().and_then(|x| x.parse().ok()).unwrap_or(2023),
+            _ => println!("Ignoring unknown argument `{}`", arg),
+        }
+    }
+    if let Some(line_number) = line_number {
+        let unfolding = (factor, separator.as_str());
+        if let Err(e) = print_arrangements(INPUT, line_number, unfolding, list, sample, seed) {
+            println!("Error: {}", e);
+        }
+    }
     match process_part_1(INPUT) {
         Ok(result) => println!("Part 1 result\n\t{}\n", result),
         Err(e) => println!("Error: {}", e),
     }
-    match process_part_2(INPUT) {
+    match process_part_2(INPUT, factor, &separator) {
         Ok(result) => println!("Part 2 result\n\t{}", result),
         Err(e) => println!("Error: {}", e),
     }
 }
 
-fn parse_line(line: &str) -> Option<(Vec<char>, Vec<i64>)> {
+fn parse_line(line: &str) -> Option<(Vec<char>, Vec<usize>)> {
     let (left, right) = line.split_once(' ')?;
     let gears = left.trim().chars().collect();
-    let numbers = right
-        .trim()
-        .split(',')
-        .map(|num| num.parse::<i64>().unwrap())
-        .collect();
+    let numbers = parse_runs(right.trim()).ok()?;
     Some((gears, numbers))
 }
 
-fn get_count(
-    memo: &mut Vec<Vec<Vec<i64>>>,
-    line: &[char],
-    index: usize,
-    prev: bool,
-    count: i64,
-    numbers: &[i64],
-    num_index: usize,
-) -> i64 {
-    if index == line.len() {
-        if num_index == numbers.len()
-            || (num_index == numbers.len() - 1 && count == numbers[num_index])
-        {
-            return 1;
-        }
-        return 0;
-    }
-    if num_index < numbers.len() && (count > numbers[num_index]) {
-        return 0;
-    }
-    if memo[index][num_index][count as usize] != -1 {
-        return memo[index][num_index][count as usize];
-    }
-    let mut result = 0;
-    if line[index] != '.' && num_index < numbers.len() {
-        result = get_count(memo, line, index + 1, true, count + 1, numbers, num_index);
-    }
-    if line[index] != '#' && !(prev && num_index < numbers.len() && numbers[num_index] != count) {
-        let mut next_index = num_index;
-        if prev {
-            next_index += 1;
-        }
-        result += get_count(memo, line, index + 1, false, 0, numbers, next_index);
-    }
-    memo[index][num_index][count as usize] = result;
-
-    result
-}
-
-fn unfold(gears: &[char], numbers: &[i64]) -> (Vec<char>, Vec<i64>) {
-    let mut ex_gears: Vec<char> = Vec::new();
-    let mut ex_numbers: Vec<i64> = Vec::new();
-    for _ in 0..5 {
-        ex_gears.extend(gears.iter());
-        ex_gears.push('?');
-        ex_numbers.extend(numbers.iter());
-    }
-    ex_gears.pop();
-
-    (ex_gears, ex_numbers)
-}
-
-fn process_part_1(input: &str) -> Result<i64, AoCError> {
+fn process_part_1(input: &str) -> Result<u128, AoCError> {
     let mut result = 0;
     for line in input.lines() {
         let (gears, numbers) = match parse_line(line) {
             Some(x) => x,
             _ => return Err(AoCError::ParsingError(line.to_string())),
         };
-        let mut memo: Vec<Vec<Vec<i64>>> =
-            vec![vec![vec![-1; gears.len() + 1]; numbers.len() + 1]; gears.len() + 1];
-        result += get_count(&mut memo, &gears, 0, false, 0, &numbers, 0);
+        result += Line::new(&gears, &numbers).count::<u128>();
     }
     Ok(result)
 }
 
-fn process_part_2(input: &str) -> Result<i64, AoCError> {
-    let mut result = 0;
+// Unfolded, the counts can outgrow any fixed width
+fn process_part_2(input: &str, factor: usize, separator: &str) -> Result<BigUint, AoCError> {
+    let mut result = BigUint::zero();
     for line in input.lines() {
         let (gears, numbers) = match parse_line(line) {
             Some(x) => x,
             _ => return Err(AoCError::ParsingError(line.to_string())),
         };
-        let (ex_gears, ex_numbers) = unfold(&gears, &numbers);
-        let mut memo: Vec<Vec<Vec<i64>>> =
-            vec![vec![vec![-1; ex_gears.len() + 1]; ex_numbers.len() + 1]; ex_gears.len() + 1];
-        result += get_count(&mut memo, &ex_gears, 0, false, 0, &ex_numbers, 0);
+        let (ex_gears, ex_numbers) = unfold(&gears, &numbers, factor, separator);
+        result += Line::new(&ex_gears, &ex_numbers).count::<BigUint>();
     }
     Ok(result)
 }
 
+// Lists the first `list` arrangements of the input's `line_number`th line, counting from 1,
+// then draws `sample` more at random
+fn print_arrangements(
+    input: &str,
+    line_number: usize,
+    (factor, separator): (usize, &str),
+    list: usize,
+    sample: usize,
+    seed: u64,
+) -> Result<(), AoCError> {
+    let line = input
+        .lines()
+        .nth(line_number.wrapping_sub(1))
+        .ok_or_else(|| AoCError::ParsingError(format!("there's no line {}", line_number)))?;
+    let (gears, numbers) = parse_line(line).ok_or(AoCError::ParsingError(line.to_string()))?;
+    let (ex_gears, ex_numbers) = unfold(&gears, &numbers, factor, separator);
+    let line = Line::new(&ex_gears, &ex_numbers);
+    let sampler = line.sampler();
+    println!(
+        "{} {:?} has {} arrangements",
+        String::from_iter(&ex_gears),
+        ex_numbers,
+        sampler.count()
+    );
+    for arrangement in line.arrangements().take(list) {
+        println!("\t{}", String::from_iter(arrangement));
+    }
+    let mut random = Random::new(seed);
+    for _ in 0..sample {
+        if let Some(arrangement) = sampler.sample(&mut random) {
+            println!("\t{} (random)", String::from_iter(arrangement));
+        }
+    }
+    println!();
+    Ok(())
+}
+
+fn solve_nonogram(rows_path: &str, cols_path: &str) -> Result<(), AoCError> {
+    let read = |path: &str| {
+        std::fs::read_to_string(path).map_err(|e| AoCError::ParsingError(format!("{path}: {e}")))
+    };
+    let nonogram =
+        Nonogram::parse(&read(rows_path)?, &read(cols_path)?).map_err(AoCError::ParsingError)?;
+    match nonogram.solve() {
+        Solution::None => println!("The nonogram has no solution\n"),
+        Solution::Unique(grid) => println!("Unique solution\n{}\n", format_grid(&grid)),
+        Solution::Multiple(first, second) => println!(
+            "More than one solution, two of them are\n{}\n\n{}\n",
+            format_grid(&first),
+            format_grid(&second)
+        ),
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     #[test]
     fn part_0() -> Result<()> {
-        let input_1 = vec!['?', '#', '#', '#', '?', '?', '?', '?', '?', '?', '?', '?'];
-        let nums_1 = vec![3, 2, 1];
-        let mut memo: Vec<Vec<Vec<i64>>> =
-            vec![vec![vec![-1; input_1.len() + 1]; nums_1.len() + 1]; input_1.len() + 1];
-        assert_eq!(get_count(&mut memo, &input_1, 0, false, 0, &nums_1, 0), 10);
-        let input_2 = vec![
-            '.', '?', '?', '.', '.', '?', '?', '.', '.', '.', '?', '#', '#', '.',
-        ];
-        let nums_2 = vec![1, 1, 3];
-        let mut memo: Vec<Vec<Vec<i64>>> =
-            vec![vec![vec![-1; input_2.len() + 1]; nums_2.len() + 1]; input_2.len() + 1];
-        assert_eq!(get_count(&mut memo, &input_2, 0, false, 0, &nums_2, 0), 4);
+        let input_1: Vec<char> = "?###????????".chars().collect();
+        assert_eq!(Line::new(&input_1, &[3, 2, 1]).count::<u64>(), 10);
+        let input_2: Vec<char> = ".??..??...?##.".chars().collect();
+        assert_eq!(Line::new(&input_2, &[1, 1, 3]).count::<u64>(), 4);
+        Ok(())
+    }
+    #[test]
+    fn deduction() -> Result<()> {
+        let deduce = |cells: &str, runs: &[usize]| {
+            let cells: Vec<char> = cells.chars().collect();
+            Line::new(&cells, runs)
+                .deduce()
+                .map(|line| line.into_iter().collect::<String>())
+        };
+        assert_eq!(deduce("??????????", &[8]).as_deref(), Some("??######??"));
+        assert_eq!(deduce("???.###", &[1, 1, 3]).as_deref(), Some("#.#.###"));
+        assert_eq!(deduce("?#?????", &[3]).as_deref(), Some("?##?..."));
+        assert_eq!(deduce("?????", &[]).as_deref(), Some("....."));
+        assert_eq!(deduce("??#??", &[1, 1, 1]).as_deref(), Some("#.#.#"));
+        assert_eq!(deduce("#.#", &[3]), None);
+        Ok(())
+    }
+    #[test]
+    fn nonograms() -> Result<()> {
+        // A heart
+        let rows = "1 1\n5\n5\n3\n1";
+        let cols = "2\n4\n4\n4\n2";
+        let solution = Nonogram::parse(rows, cols).map_err(AoCError::ParsingError)?;
+        let Solution::Unique(grid) = solution.solve() else {
+            panic!("the heart has one solution");
+        };
+        assert_eq!(format_grid(&grid), ".#.#.\n#####\n#####\n.###.\n..#..");
+
+        // Needs a guess, and either diagonal works
+        let nonogram = Nonogram::parse("1\n1", "1\n1").map_err(AoCError::ParsingError)?;
+        let Solution::Multiple(first, second) = nonogram.solve() else {
+            panic!("the diagonals are both solutions");
+        };
+        assert_eq!(format_grid(&first), "#.\n.#");
+        assert_eq!(format_grid(&second), ".#\n#.");
+
+        let nonogram = Nonogram::parse("2\n0", "1\n0").map_err(AoCError::ParsingError)?;
+        assert_eq!(nonogram.solve(), Solution::None);
+        assert!(Nonogram::parse("1,x", "1").is_err());
+        assert_eq!(parse_runs("0"), Ok(vec![]));
+        assert_eq!(parse_runs(""), Ok(vec![]));
+        assert_eq!(parse_runs("1, 2 3"), Ok(vec![1, 2, 3]));
+        assert!(parse_runs("1,0,2").is_err());
+        assert!(parse_runs("0 0").is_err());
         Ok(())
     }
     #[test]
@@ -163,7 +230,47 @@ mod tests {
 ????.#...#... 4,1,1
 ????.######..#####. 1,6,5
 ?###???????? 3,2,1";
-        assert_eq!(process_part_2(input_1)?, 525152);
+        assert_eq!(process_part_2(input_1, 5, "?")?, BigUint::from(525152u32));
+        assert_eq!(process_part_2(input_1, 1, "?")?, BigUint::from(21u32));
+        Ok(())
+    }
+    #[test]
+    fn arrangements() -> Result<()> {
+        let cells: Vec<char> = "?###????????".chars().collect();
+        let line = Line::new(&cells, &[3, 2, 1]);
+        let all: Vec<String> = line.arrangements().map(String::from_iter).collect();
+        assert_eq!(all.len(), 10);
+        assert_eq!(all[0], ".###....##.#");
+        assert_eq!(all[9], ".###.##.#...");
+        let mut sorted = all.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted.len(), 10);
+        assert_eq!(Line::new(&['#', '.'], &[2]).arrangements().count(), 0);
+
+        // Every arrangement turns up when sampling, about as often as the others
+        let sampler = line.sampler();
+        let mut random = Random::new(7);
+        let mut seen = std::collections::HashMap::new();
+        for _ in 0..5000 {
+            let arrangement = String::from_iter(sampler.sample(&mut random).unwrap());
+            *seen.entry(arrangement).or_insert(0) += 1;
+        }
+        assert_eq!(seen.len(), 10);
+        assert!(seen.keys().all(|arrangement| all.contains(arrangement)));
+        assert!(seen.values().all(|count| (400..600).contains(count)));
+
+        // Far beyond u128
+        let (cells, runs) = unfold(&['?'; 3], &[1], 60, "?");
+        let count = Line::new(&cells, &runs).count::<BigUint>();
+        assert!(count.bits() > 128);
+        assert_eq!(Line::new(&cells, &runs).sampler().count(), &count);
+
+        assert_eq!(
+            unfold(&['#', '?'], &[1], 3, ".."),
+            ("#?..#?..#?".chars().collect(), vec![1, 1, 1])
+        );
+        assert_eq!(unfold(&['#'], &[1], 2, "").0, vec!['#', '#']);
         Ok(())
     }
 }
@@ -11,201 +11,171 @@ pub enum AoCError {
     Unknown,
 }
 
-#[derive(Copy, Clone)]
-struct Grid<'a> {
-    data: &'a [u8],
-    width: usize,
-    height: usize,
+// Each row and column stored as a bitmask of its `#`s, so comparing two lines is an xor
+struct Pattern {
+    rows: Vec<u64>,
+    cols: Vec<u64>,
 }
 
-impl Grid<'_> {
-    pub fn new(data: &str) -> Grid {
-        let height = data.lines().count();
-        let width = data.lines().next().unwrap().chars().count();
-        Grid {
-            data: data.as_bytes(),
-            width,
-            height,
+impl Pattern {
+    pub fn new(data: &str) -> Result<Pattern, AoCError> {
+        let lines: Vec<&str> = data.lines().collect();
+        let width = lines.first().map_or(0, |line| line.len());
+        if width == 0 || width > 64 || lines.len() > 64 {
+            return Err(AoCError::ParsingError(data.to_string()));
         }
-    }
-    pub fn get(&self, x: i32, y: i32) -> Option<char> {
-        if x >= (self.width as i32) || y >= (self.height as i32) || x < 0 || y < 0 {
-            return None;
+        let mut rows = vec![0; lines.len()];
+        let mut cols = vec![0; width];
+        for (row, line) in lines.iter().enumerate() {
+            if line.len() != width {
+                return Err(AoCError::ParsingError(line.to_string()));
+            }
+            for (col, block) in line.bytes().enumerate() {
+                match block {
+                    b'#' => {
+                        rows[row] |= 1 << col;
+                        cols[col] |= 1 << row;
+                    }
+                    b'.' => {}
+                    _ => return Err(AoCError::ParsingError(line.to_string())),
+                }
+            }
         }
-        let index = ((y as usize) * (self.width + 1)) + (x as usize);
-        Some(self.data[index] as char)
-    }
-}
-
-fn main() {
-    const INPUT: &str = include_str!("./input.txt");
-    println!(
-        "\n🎄🎄🎄🎄🎄 Advent of Code ||| Day {} 🎄🎄🎄🎄🎄\n",
-        AOC_DAY
-    );
-    match process_part_1(INPUT) {
-        Ok(result) => println!("Part 1 result\n\t{}\n", result),
-        Err(e) => println!("Error: {}", e),
-    }
-    match process_part_2(INPUT) {
-        Ok(result) => println!("Part 2 result\n\t{}", result),
-        Err(e) => println!("Error: {}", e),
+        Ok(Pattern { rows, cols })
     }
 }
 
-fn cols_eq(grid: &Grid, a: i32, b: i32) -> (bool, i32) {
-    let mut diff = 0;
-    for index in 0..grid.height {
-        let row = index as i32;
-        let left = match grid.get(a, row) {
-            Some(x) => x,
-            _ => panic!(),
-        };
-        let right = match grid.get(b, row) {
-            Some(x) => x,
-            _ => panic!(),
-        };
-        if left != right {
-            diff += 1;
-        }
-    }
-    if diff > 0 {
-        return (false, diff);
-    }
-    (true, diff)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Axis {
+    // A line between two rows
+    Horizontal,
+    // A line between two columns
+    Vertical,
 }
 
-fn check_cols_mirrored(grid: &Grid, a: i32, b: i32) -> bool {
-    let mut left = a;
-    let mut right = b;
-    while left >= 0 && right < grid.width as i32 && cols_eq(grid, left, right).0 {
-        left -= 1;
-        right += 1;
-    }
-    if left == -1 || right == grid.width as i32 {
-        return true;
-    }
-    false
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Reflection {
+    axis: Axis,
+    // Number of rows above, or columns left of, the line
+    position: usize,
+    differences: u32,
+    // (row, col) of each pair of mirrored cells that don't match, flipping either one fixes it
+    smudges: Vec<[(usize, usize); 2]>,
 }
 
-fn rows_eq(grid: &Grid, a: i32, b: i32) -> (bool, i32) {
-    let mut diff = 0;
-    for index in 0..grid.width {
-        let col = index as i32;
-        let left = match grid.get(col, a) {
-            Some(x) => x,
-            _ => panic!(),
-        };
-        let right = match grid.get(col, b) {
-            Some(x) => x,
-            _ => panic!(),
-        };
-        if left != right {
-            diff += 1;
+impl Reflection {
+    fn score(&self) -> usize {
+        match self.axis {
+            Axis::Horizontal => self.position * 100,
+            Axis::Vertical => self.position,
         }
     }
-    if diff > 0 {
-        return (false, diff);
-    }
-    (true, diff)
 }
 
-fn check_rows_mirrored(grid: &Grid, a: i32, b: i32) -> bool {
-    let mut top = a;
-    let mut bot = b;
-    while top >= 0 && bot < grid.height as i32 && rows_eq(grid, top, bot).0 {
-        top -= 1;
-        bot += 1;
-    }
-    if top == -1 || bot == grid.height as i32 {
-        return true;
-    }
-    false
+// Positions where `lines` mirror with at most `max_smudges` differing cells, and how many differ
+fn mirror_lines(lines: &[u64], max_smudges: u32) -> Vec<(usize, u32)> {
+    (1..lines.len())
+        .filter_map(|position| {
+            let mut differences = 0;
+            for (a, b) in lines[..position].iter().rev().zip(&lines[position..]) {
+                differences += (a ^ b).count_ones();
+                if differences > max_smudges {
+                    return None;
+                }
+            }
+            Some((position, differences))
+        })
+        .collect()
 }
 
-fn check_rows_mirrored_2(grid: &Grid, a: i32, b: i32) -> bool {
-    let mut top = a;
-    let mut bot = b;
-    let mut smudge = 0;
-    while top >= 0 && bot < grid.height as i32 && smudge < 2 {
-        let result = rows_eq(grid, top, bot);
-        if !result.0 {
-            smudge += result.1;
+fn smudges(lines: &[u64], position: usize) -> Vec<[usize; 3]> {
+    let mut result = Vec::new();
+    for (index, (a, b)) in lines[..position]
+        .iter()
+        .rev()
+        .zip(&lines[position..])
+        .enumerate()
+    {
+        let mut diff = a ^ b;
+        while diff != 0 {
+            let cell = diff.trailing_zeros() as usize;
+            result.push([position - 1 - index, position + index, cell]);
+            diff &= diff - 1;
         }
-        top -= 1;
-        bot += 1;
     }
-    if (top == -1 || bot == grid.height as i32) && smudge == 1 {
-        return true;
-    }
-    false
+    result
 }
 
-fn check_cols_mirrored_2(grid: &Grid, a: i32, b: i32) -> bool {
-    let mut left = a;
-    let mut right = b;
-    let mut smudge = 0;
-    while left >= 0 && right < grid.width as i32 && smudge < 2 {
-        let result = cols_eq(grid, left, right);
-        if !result.0 {
-            smudge += result.1;
-        }
-        left -= 1;
-        right += 1;
-    }
-    if (left == -1 || right == grid.width as i32) && smudge == 1 {
-        return true;
-    }
-    false
+fn find_reflections(pattern: &Pattern, max_smudges: u32) -> Vec<Reflection> {
+    let horizontal =
+        mirror_lines(&pattern.rows, max_smudges)
+            .into_iter()
+            .map(|(position, differences)| Reflection {
+                axis: Axis::Horizontal,
+                position,
+                differences,
+                smudges: smudges(&pattern.rows, position)
+                    .into_iter()
+                    .map(|[top, bottom, col]| [(top, col), (bottom, col)])
+                    .collect(),
+            });
+    let vertical =
+        mirror_lines(&pattern.cols, max_smudges)
+            .into_iter()
+            .map(|(position, differences)| Reflection {
+                axis: Axis::Vertical,
+                position,
+                differences,
+                smudges: smudges(&pattern.cols, position)
+                    .into_iter()
+                    .map(|[left, right, row]| [(row, left), (row, right)])
+                    .collect(),
+            });
+    horizontal.chain(vertical).collect()
 }
 
-fn check_mirrored(grid: &Grid, check: fn(&Grid, i32, i32) -> bool, limit: i32) -> Option<i32> {
-    let mut index = 1;
-    while index < limit {
-        if check(grid, index - 1, index) {
-            return Some(index);
-        }
-        index += 1;
-    }
-    None
+fn parse_patterns(input: &str) -> Result<Vec<Pattern>, AoCError> {
+    input
+        .split("\n\n")
+        .filter(|pattern| !pattern.trim().is_empty())
+        .map(Pattern::new)
+        .collect()
 }
 
-fn process_part_1(input: &str) -> Result<i32, AoCError> {
-    let grids: Vec<Grid> = input.split("\n\n").map(|grid| Grid::new(grid)).collect();
-    let result = grids
+// Sums the reflections that need exactly `smudges` cells fixed
+fn summarize(input: &str, smudges: u32) -> Result<usize, AoCError> {
+    let patterns = parse_patterns(input)?;
+    let result = patterns
         .iter()
-        .map(|grid| {
-            let col_mirror = match check_mirrored(grid, check_cols_mirrored, grid.width as i32) {
-                Some(x) => x,
-                _ => 0,
-            };
-            let row_mirror = match check_mirrored(grid, check_rows_mirrored, grid.height as i32) {
-                Some(x) => x,
-                _ => 0,
-            };
-            col_mirror + (row_mirror * 100)
-        })
+        .flat_map(|pattern| find_reflections(pattern, smudges))
+        .filter(|reflection| reflection.differences == smudges)
+        .map(|reflection| reflection.score())
         .sum();
     Ok(result)
 }
 
-fn process_part_2(input: &str) -> Result<i32, AoCError> {
-    let grids: Vec<Grid> = input.split("\n\n").map(|grid| Grid::new(grid)).collect();
-    let result = grids
-        .iter()
-        .map(|grid| {
-            let col_mirror = match check_mirrored(grid, check_cols_mirrored_2, grid.width as i32) {
-                Some(x) => x,
-                _ => 0,
-            };
-            let row_mirror = match check_mirrored(grid, check_rows_mirrored_2, grid.height as i32) {
-                Some(x) => x,
-                _ => 0,
-            };
-            col_mirror + (row_mirror * 100)
-        })
-        .sum();
-    Ok(result)
+fn main() {
+    const INPUT: &str = include_str!("./input.txt");
+    println!(
+        "\n🎄🎄🎄🎄🎄 Advent of Code ||| Day {} 🎄🎄🎄🎄🎄\n",
+        AOC_DAY
+    );
+    match process_part_1(INPUT) {
+        Ok(result) => println!("Part 1 result\n\t{}\n", result),
+        Err(e) => println!("Error: {}", e),
+    }
+    match process_part_2(INPUT) {
+        Ok(result) => println!("Part 2 result\n\t{}", result),
+        Err(e) => println!("Error: {}", e),
+    }
+}
+
+fn process_part_1(input: &str) -> Result<usize, AoCError> {
+    summarize(input, 0)
+}
+
+fn process_part_2(input: &str) -> Result<usize, AoCError> {
+    summarize(input, 1)
 }
 
 #[cfg(test)]
@@ -251,4 +221,57 @@ mod tests {
         assert_eq!(process_part_2(input_1)?, 400);
         Ok(())
     }
+    #[test]
+    fn reflections() -> Result<()> {
+        let patterns = parse_patterns(
+            "#.##..##.
+..#.##.#.
+##......#
+##......#
+..#.##.#.
+..##..##.
+#.#.##.#.
+
+#...##..#
+#....#..#
+..##..###
+#####.##.
+#####.##.
+..##..###
+#....#..#",
+        )?;
+        let first = find_reflections(&patterns[0], 1);
+        assert_eq!(
+            first,
+            vec![
+                Reflection {
+                    axis: Axis::Horizontal,
+                    position: 3,
+                    differences: 1,
+                    smudges: vec![[(0, 0), (5, 0)]],
+                },
+                Reflection {
+                    axis: Axis::Vertical,
+                    position: 5,
+                    differences: 0,
+                    smudges: vec![],
+                },
+            ]
+        );
+        let second = find_reflections(&patterns[1], 1);
+        assert!(second.contains(&Reflection {
+            axis: Axis::Horizontal,
+            position: 1,
+            differences: 1,
+            smudges: vec![[(0, 4), (1, 4)]],
+        }));
+        // Allowing more smudges finds more lines, each with its exact count
+        let loose = find_reflections(&patterns[0], 3);
+        assert!(loose.len() > first.len());
+        assert!(loose
+            .iter()
+            .all(|r| r.differences == r.smudges.len() as u32));
+        assert!(parse_patterns("#.#\n#.").is_err());
+        Ok(())
+    }
 }
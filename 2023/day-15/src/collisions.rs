@@ -0,0 +1,102 @@
+use crate::{check_ascii, hash_bytes, HashError};
+use std::collections::HashSet;
+
+// 17 * 241 = 4097, which is 1 more than a multiple of 256
+const INVERSE_17: usize = 241;
+
+// Which labels land in which bucket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadReport<'a> {
+    buckets: Vec<Vec<&'a str>>,
+}
+
+impl<'a> LoadReport<'a> {
+    // Repeated labels only count once, in the bucket where they were first seen
+    pub fn new(
+        labels: impl IntoIterator<Item = &'a str>,
+        count: usize,
+        hash: impl Fn(&str) -> usize,
+    ) -> LoadReport<'a> {
+        let mut buckets = vec![Vec::new(); count];
+        let mut seen = HashSet::new();
+        for label in labels {
+            if seen.insert(label) {
+                buckets[hash(label) % count].push(label);
+            }
+        }
+        LoadReport { buckets }
+    }
+
+    pub fn bucket(&self, number: usize) -> &[&'a str] {
+        &self.buckets[number]
+    }
+
+    pub fn labels(&self) -> usize {
+        self.buckets.iter().map(Vec::len).sum()
+    }
+
+    pub fn max_load(&self) -> usize {
+        self.buckets.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    // histogram[k] is how many buckets hold k labels
+    pub fn histogram(&self) -> Vec<usize> {
+        let mut histogram = vec![0; self.max_load() + 1];
+        for bucket in self.buckets.iter() {
+            histogram[bucket.len()] += 1;
+        }
+        histogram
+    }
+
+    // Every bucket holding more than one label, with the labels in the order they arrived
+    pub fn chains(&self) -> impl Iterator<Item = (usize, &[&'a str])> {
+        self.buckets
+            .iter()
+            .enumerate()
+            .filter(|(_, bucket)| bucket.len() > 1)
+            .map(|(number, bucket)| (number, bucket.as_slice()))
+    }
+}
+
+// Labels of `length` characters from `alphabet` whose HASH is `target`, in alphabetical order.
+// HASH can be run backwards, so only the prefixes are enumerated: the last character is whichever
+// one takes the prefix's value to the target, if the alphabet has it.
+pub fn labels_hashing_to(
+    target: usize,
+    length: usize,
+    alphabet: &str,
+) -> Result<impl Iterator<Item = String>, HashError> {
+    if target >= 256 {
+        return Err(HashError::OutOfRange(target));
+    }
+    check_ascii(alphabet)?;
+    let mut letters: Vec<u8> = alphabet.bytes().collect();
+    letters.sort_unstable();
+    letters.dedup();
+    let prefix_length = length.saturating_sub(1);
+    let total = (letters.len() as u128).checked_pow(prefix_length as u32);
+    let prefixes: u128 = match (length, total) {
+        (0, _) => 0,
+        (_, Some(total)) => total,
+        (_, None) => u128::MAX,
+    };
+    let empty = (length == 0 && target == 0).then(String::new);
+    Ok(empty
+        .into_iter()
+        .chain((0..prefixes).filter_map(move |index| {
+            // The prefix is `index` written in base `letters.len()`, most significant letter first
+            let mut prefix = vec![0u8; prefix_length];
+            let mut rest = index;
+            for slot in prefix.iter_mut().rev() {
+                *slot = letters[(rest % letters.len() as u128) as usize];
+                rest /= letters.len() as u128;
+            }
+            let value = hash_bytes(&prefix);
+            let last = (target * INVERSE_17 + 256 - value) % 256;
+            let last = u8::try_from(last)
+                .ok()
+                .filter(|byte| letters.contains(byte))?;
+            prefix.push(last);
+            String::from_utf8(prefix).ok()
+        })))
+}
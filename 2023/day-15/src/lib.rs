@@ -0,0 +1,184 @@
+use ordered_hash_map::OrderedHashMap;
+use std::num::NonZeroUsize;
+use thiserror::Error;
+
+pub mod collisions;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum HashError {
+    #[error("`{ch}` at position {position} isn't ASCII")]
+    NonAscii { ch: char, position: usize },
+    #[error("`{0}` isn't a HASH value, they run from 0 to 255")]
+    OutOfRange(usize),
+}
+
+// One character's worth of HASH, with the value after each part of it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashStep {
+    pub byte: u8,
+    pub increased: usize,
+    pub multiplied: usize,
+    pub value: usize,
+}
+
+// The puzzle's HASH a byte at a time: add the byte, multiply by 17 and keep the remainder by 256
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Hasher {
+    value: usize,
+}
+
+impl Hasher {
+    pub fn update(&mut self, byte: u8) -> HashStep {
+        let increased = self.value + byte as usize;
+        let multiplied = increased * 17;
+        self.value = multiplied % 256;
+        HashStep {
+            byte,
+            increased,
+            multiplied,
+            value: self.value,
+        }
+    }
+
+    pub fn value(&self) -> usize {
+        self.value
+    }
+}
+
+pub fn check_ascii(input: &str) -> Result<(), HashError> {
+    match input.char_indices().find(|(_, ch)| !ch.is_ascii()) {
+        Some((position, ch)) => Err(HashError::NonAscii { ch, position }),
+        None => Ok(()),
+    }
+}
+
+// HASH is defined on ASCII codes, so anything else is an error rather than hashed some other way
+pub fn hash(input: &str) -> Result<usize, HashError> {
+    Ok(hash_steps(input)?.last().map_or(0, |step| step.value))
+}
+
+pub fn hash_steps(input: &str) -> Result<Vec<HashStep>, HashError> {
+    check_ascii(input)?;
+    let mut hasher = Hasher::default();
+    Ok(input.bytes().map(|byte| hasher.update(byte)).collect())
+}
+
+// HASH over the raw bytes, for labels already known to be ASCII
+pub fn hash_bytes(bytes: &[u8]) -> usize {
+    let mut hasher = Hasher::default();
+    bytes.iter().for_each(|byte| {
+        hasher.update(*byte);
+    });
+    hasher.value()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation<'a> {
+    // `label-`
+    Remove(&'a str),
+    // `label=focal length`
+    Insert(&'a str, u32),
+}
+
+impl Operation<'_> {
+    pub fn parse(step: &str) -> Result<Operation<'_>, String> {
+        check_ascii(step).map_err(|e| e.to_string())?;
+        if let Some(label) = step.strip_suffix('-') {
+            return Ok(Operation::Remove(label));
+        }
+        let (label, focal_length) = step
+            .split_once('=')
+            .ok_or_else(|| format!("`{}` is neither `label-` nor `label=focal length`", step))?;
+        let focal_length = focal_length
+            .parse()
+            .map_err(|_| format!("`{}` isn't a focal length", focal_length))?;
+        Ok(Operation::Insert(label, focal_length))
+    }
+
+    pub fn label(&self) -> &str {
+        match self {
+            Operation::Remove(label) | Operation::Insert(label, _) => label,
+        }
+    }
+}
+
+// Boxes of lenses in the order they went in, each lens in the box its label hashes to
+#[derive(Debug, Clone)]
+pub struct LensBoxes<H> {
+    hash: H,
+    boxes: Vec<OrderedHashMap<String, u32>>,
+}
+
+const BOX_COUNT: NonZeroUsize = NonZeroUsize::new(256).unwrap();
+
+impl LensBoxes<fn(&str) -> usize> {
+    // The puzzle's 256 boxes and HASH. Parsed operations only have ASCII labels.
+    pub fn standard() -> Self {
+        LensBoxes::new(|label: &str| hash_bytes(label.as_bytes()), BOX_COUNT)
+    }
+}
+
+impl<H: Fn(&str) -> usize> LensBoxes<H> {
+    // Labels go in box `hash(label) % count`
+    pub fn new(hash: H, count: NonZeroUsize) -> Self {
+        LensBoxes {
+            hash,
+            boxes: vec![OrderedHashMap::new(); count.get()],
+        }
+    }
+
+    pub fn box_of(&self, label: &str) -> usize {
+        (self.hash)(label) % self.boxes.len()
+    }
+
+    pub fn apply(&mut self, operation: &Operation) {
+        let number = self.box_of(operation.label());
+        let lenses = &mut self.boxes[number];
+        match operation {
+            Operation::Remove(label) => {
+                lenses.remove(*label);
+            }
+            // A lens with the same label is swapped in place, otherwise the new one goes last
+            Operation::Insert(label, focal_length) => match lenses.get_mut(*label) {
+                Some(current) => *current = *focal_length,
+                None => {
+                    lenses.insert(label.to_string(), *focal_length);
+                }
+            },
+        }
+    }
+
+    pub fn lenses(&self, number: usize) -> impl Iterator<Item = (&str, u32)> {
+        self.boxes[number]
+            .iter()
+            .map(|(label, focal_length)| (label.as_str(), *focal_length))
+    }
+
+    // One more than the box number, times the slot counting from 1, times the focal length
+    pub fn focusing_power(&self) -> usize {
+        (0..self.boxes.len())
+            .flat_map(|number| {
+                self.lenses(number)
+                    .enumerate()
+                    .map(move |(slot, (_, focal_length))| {
+                        (number + 1) * (slot + 1) * focal_length as usize
+                    })
+            })
+            .sum()
+    }
+
+    // The boxes holding lenses, like the puzzle's walkthrough: `Box 0: [rn 1] [cm 2]`
+    pub fn dump(&self) -> String {
+        (0..self.boxes.len())
+            .filter(|number| !self.boxes[*number].is_empty())
+            .map(|number| {
+                let lenses: Vec<String> = self
+                    .lenses(number)
+                    .map(|(label, focal_length)| format!("[{} {}]", label, focal_length))
+                    .collect();
+                format!("Box {}: {}", number, lenses.join(" "))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
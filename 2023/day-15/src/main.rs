@@ -1,6 +1,6 @@
 use anyhow::Result;
-use ordered_hash_map::OrderedHashMap;
-use std::collections::HashMap;
+use day_15::collisions::{labels_hashing_to, LoadReport};
+use day_15::{hash, hash_bytes, hash_steps, HashError, LensBoxes, Operation};
 use thiserror::Error;
 extern crate pretty_env_logger;
 #[macro_use]
@@ -12,6 +12,8 @@ const AOC_DAY: &str = "15";
 pub enum AoCError {
     #[error("Unable to parse the input `{0}`")]
     ParsingError(String),
+    #[error("Unable to hash: {0}")]
+    HashError(#[from] HashError),
     #[error("An unknown error has occurred (super duper helpful error)")]
     Unknown,
 }
@@ -23,6 +25,42 @@ fn main() {
         "\n🎄🎄🎄🎄🎄 Advent of Code ||| Day {} 🎄🎄🎄🎄🎄\n",
         AOC_DAY
     );
+    // Usage: day-15 [--walkthrough] [--explain <text>] [--loads [<buckets>]]
+    //               [--search <bucket> <length> [<alphabet>]]
+    let mut args = std::env::args().skip(1).peekable();
+    while let Some(arg) = args.next() {
+        let result = match arg.as_str() {
+            "--walkthrough" => walkthrough(INPUT).map(|steps| print!("{}", steps)),
+            "--explain" => {
+                explain(&args.next().unwrap_or_default()).map(|steps| println!("{}", steps))
+            }
+            "--loads" => {
+                let buckets = args.next_if(|x| x.parse::<usize>().is_ok());
+                print_loads(INPUT, buckets.map_or(256, |x| x.parse().unwrap()))
+            }
+            "--search" => {
+                let target = args.next().and_then(|x| x.parse().ok());
+                let length = args.next().and_then(|x| x.parse().ok());
+                let alphabet = args.next_if(|x| !x.starts_with("--"));
+                match (target, length) {
+                    (Some(target), Some(length)) => {
+                        let alphabet = alphabet.as_deref().unwrap_or("abcdefghijklmnopqrstuvwxyz");
+                        print_search(target, length, alphabet)
+                    }
+                    _ => Err(AoCError::ParsingError(
+                        "`--search` needs a bucket and a length".to_string(),
+                    )),
+                }
+            }
+            _ => {
+                println!("Ignoring unknown argument `{}`", arg);
+                Ok(())
+            }
+        };
+        if let Err(e) = result {
+            println!("Error: {}", e);
+        }
+    }
     match process_part_1(INPUT) {
         Ok(result) => println!("Part 1 result\n\t{}\n", result),
         Err(e) => println!("Error: {}", e),
@@ -33,78 +71,101 @@ fn main() {
     }
 }
 
-fn hash(input: &str) -> i64 {
-    input
-        .chars()
-        .fold(0, |acc, ch| ((acc + ch as i64) * 17) % 256)
+fn process_part_1(input: &str) -> Result<usize, AoCError> {
+    let mut result = 0;
+    for step in input.split(',') {
+        result += hash(step)?;
+    }
+    Ok(result)
 }
 
-fn process_part_1(input: &str) -> Result<i64, AoCError> {
-    let result = input.split(',').map(hash).sum();
-    Ok(result)
+// HASH worked through a character at a time, in the puzzle's words
+fn explain(input: &str) -> Result<String, AoCError> {
+    let mut lines = vec!["The current value starts at 0.".to_string()];
+    for step in hash_steps(input)? {
+        lines.push(format!(
+            "The next character is {}; its ASCII code is {}.",
+            step.byte as char, step.byte
+        ));
+        lines.push(format!(
+            "The current value increases to {}.",
+            step.increased
+        ));
+        lines.push(format!(
+            "The current value is multiplied by 17 to become {}.",
+            step.multiplied
+        ));
+        lines.push(format!(
+            "The current value becomes {} (the remainder of {} divided by 256).",
+            step.value, step.multiplied
+        ));
+    }
+    Ok(lines.join("\n"))
 }
 
-fn process_command<'a>(
-    input: &'a str,
-    box_array: &mut [OrderedHashMap<&'a str, i32>],
-    label_map: &mut HashMap<&'a str, usize>,
-) -> Result<(), AoCError> {
-    if input.ends_with('-') {
-        let label = &input[0..input.len() - 1];
-        let box_result = label_map.remove(label);
-        if let Some(box_num) = box_result {
-            box_array[box_num].remove(label);
-        }
-    } else {
-        let (label, f_size) = match input.split_once('=') {
-            Some(x) => x,
-            _ => return Err(AoCError::ParsingError(input.to_string())),
-        };
-        let f_number = f_size.parse::<i32>().unwrap();
-        // Update or add
-        let box_result = label_map.get(label);
-        if let Some(box_num) = box_result {
-            let current_value = match box_array[*box_num].get_mut(label) {
-                Some(x) => x,
-                _ => return Err(AoCError::ParsingError(input.to_string())),
-            };
-            *current_value = f_number;
-            return Ok(());
-        }
-        let box_num = hash(label) as usize;
-        trace!("Adding {}, {} to box {}", label, f_number, box_num);
-        label_map.insert(label, box_num);
-        box_array[box_num].insert(label, f_number);
+fn print_loads(input: &str, buckets: usize) -> Result<(), AoCError> {
+    if buckets == 0 {
+        return Err(AoCError::ParsingError(
+            "there has to be at least one bucket".to_string(),
+        ));
     }
+    let steps = parse_steps(input)?;
+    let report = LoadReport::new(steps.iter().map(Operation::label), buckets, |label| {
+        hash_bytes(label.as_bytes())
+    });
+    println!("{} labels in {} buckets", report.labels(), buckets);
+    for (load, count) in report.histogram().iter().enumerate() {
+        println!("\t{} buckets hold {} labels", count, load);
+    }
+    for (number, chain) in report.chains() {
+        println!("\tBucket {}: {}", number, chain.join(" -> "));
+    }
+    println!();
     Ok(())
 }
 
-fn process_part_2(input: &str) -> Result<i64, AoCError> {
-    let mut box_array: Vec<OrderedHashMap<&str, i32>> = vec![OrderedHashMap::new(); 256];
-    let mut label_map: HashMap<&str, usize> = HashMap::new(); // Label -> Box# storage
-    for command in input.split(',') {
-        process_command(command, &mut box_array, &mut label_map)?;
-    }
-    let result = box_array
-        .iter()
-        .enumerate()
-        .filter(|(_, box_map)| !box_map.is_empty())
-        .fold(0, |acc_1, (box_num, box_map)| {
-            let box_count = box_map
-                .values()
-                .enumerate()
-                .fold(0, |acc_2, (slot, f_length)| {
-                    acc_2 + ((box_num + 1) * (slot + 1) * *f_length as usize)
-                });
-            info!("Box: {}, total = {}", box_num + 1, box_count);
-            acc_1 + box_count
-        });
-    Ok(result as i64)
+fn print_search(target: usize, length: usize, alphabet: &str) -> Result<(), AoCError> {
+    let labels = labels_hashing_to(target, length, alphabet)?;
+    println!("Labels of length {} hashing to {}:", length, target);
+    for label in labels.take(20) {
+        println!("\t{}", label);
+    }
+    println!();
+    Ok(())
+}
+
+fn parse_steps(input: &str) -> Result<Vec<Operation<'_>>, AoCError> {
+    input
+        .trim()
+        .split(',')
+        .map(|step| Operation::parse(step).map_err(AoCError::ParsingError))
+        .collect()
+}
+
+fn process_part_2(input: &str) -> Result<usize, AoCError> {
+    let mut boxes = LensBoxes::standard();
+    for operation in parse_steps(input)? {
+        boxes.apply(&operation);
+        trace!("After {:?}:\n{}", operation, boxes.dump());
+    }
+    Ok(boxes.focusing_power())
+}
+
+// The boxes after every step, laid out like the puzzle's walkthrough
+fn walkthrough(input: &str) -> Result<String, AoCError> {
+    let mut boxes = LensBoxes::standard();
+    let mut result = String::new();
+    for (step, operation) in input.trim().split(',').zip(parse_steps(input)?) {
+        boxes.apply(&operation);
+        result.push_str(&format!("After \"{}\":\n{}\n\n", step, boxes.dump()));
+    }
+    Ok(result)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::num::NonZeroUsize;
     #[test]
     fn part_1() -> Result<()> {
         // pretty_env_logger::init();
@@ -119,4 +180,143 @@ mod tests {
         assert_eq!(process_part_2(input_1)?, 145);
         Ok(())
     }
+    #[test]
+    fn lens_boxes() -> Result<()> {
+        let input_1 = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
+        let expected = r#"After "rn=1":
+Box 0: [rn 1]
+
+After "cm-":
+Box 0: [rn 1]
+
+After "qp=3":
+Box 0: [rn 1]
+Box 1: [qp 3]
+
+After "cm=2":
+Box 0: [rn 1] [cm 2]
+Box 1: [qp 3]
+
+After "qp-":
+Box 0: [rn 1] [cm 2]
+
+After "pc=4":
+Box 0: [rn 1] [cm 2]
+Box 3: [pc 4]
+
+After "ot=9":
+Box 0: [rn 1] [cm 2]
+Box 3: [pc 4] [ot 9]
+
+After "ab=5":
+Box 0: [rn 1] [cm 2]
+Box 3: [pc 4] [ot 9] [ab 5]
+
+After "pc-":
+Box 0: [rn 1] [cm 2]
+Box 3: [ot 9] [ab 5]
+
+After "pc=6":
+Box 0: [rn 1] [cm 2]
+Box 3: [ot 9] [ab 5] [pc 6]
+
+After "ot=7":
+Box 0: [rn 1] [cm 2]
+Box 3: [ot 7] [ab 5] [pc 6]
+
+"#;
+        assert_eq!(walkthrough(input_1)?, expected);
+
+        // Every label in one box, so the power only depends on the order they went in
+        let mut boxes = LensBoxes::new(|_: &str| 0, NonZeroUsize::MIN);
+        for operation in parse_steps(input_1)? {
+            boxes.apply(&operation);
+        }
+        assert_eq!(boxes.dump(), "Box 0: [rn 1] [cm 2] [ot 7] [ab 5] [pc 6]");
+        assert_eq!(boxes.focusing_power(), 1 + 2 * 2 + 3 * 7 + 4 * 5 + 5 * 6);
+
+        let mut boxes = LensBoxes::new(str::len, NonZeroUsize::new(4).unwrap());
+        boxes.apply(&Operation::parse("abcde=3").unwrap());
+        assert_eq!(boxes.box_of("abcde"), 1);
+        assert_eq!(boxes.focusing_power(), 2 * 3);
+        assert!(parse_steps("rn=1,cm").is_err());
+        assert!(parse_steps("rn=x").is_err());
+        Ok(())
+    }
+    #[test]
+    fn hashing() -> Result<()> {
+        assert_eq!(hash("HASH")?, 52);
+        assert_eq!(hash("")?, 0);
+        let steps = hash_steps("HASH")?;
+        assert_eq!(steps[0].increased, 72);
+        assert_eq!(steps[0].multiplied, 1224);
+        assert_eq!(steps[0].value, 200);
+        assert!(explain("H")?
+            .ends_with("The current value becomes 200 (the remainder of 1224 divided by 256)."));
+        assert_eq!(
+            hash("rn=é"),
+            Err(HashError::NonAscii {
+                ch: 'é',
+                position: 3
+            })
+        );
+        assert!(matches!(
+            process_part_1("rn=1,cm-,ō=1"),
+            Err(AoCError::HashError(_))
+        ));
+        assert!(parse_steps("rn=1,cmé-").is_err());
+        Ok(())
+    }
+    #[test]
+    fn collisions() -> Result<()> {
+        let input_1 = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
+        let steps = parse_steps(input_1)?;
+        let report = LoadReport::new(steps.iter().map(Operation::label), 256, |label| {
+            hash_bytes(label.as_bytes())
+        });
+        assert_eq!(report.labels(), 6);
+        assert_eq!(report.max_load(), 3);
+        assert_eq!(report.histogram(), vec![253, 1, 1, 1]);
+        let chains: Vec<_> = report.chains().collect();
+        assert_eq!(
+            chains,
+            vec![(0, &["rn", "cm"][..]), (3, &["pc", "ot", "ab"][..])]
+        );
+        assert_eq!(report.bucket(1), ["qp"]);
+
+        // Fewer buckets than HASH gives
+        let report = LoadReport::new(["rn", "qp", "pc"], 2, |label| hash_bytes(label.as_bytes()));
+        assert_eq!(report.bucket(1), ["qp", "pc"]);
+
+        let labels: Vec<String> = labels_hashing_to(3, 2, "abcdefghijklmnopqrstuvwxyz")?.collect();
+        assert!(labels.contains(&"pc".to_string()) && labels.contains(&"ot".to_string()));
+        assert!(labels.windows(2).all(|pair| pair[0] < pair[1]));
+        for label in labels.iter() {
+            assert_eq!(hash(label)?, 3);
+        }
+        let brute = (b'a'..=b'z')
+            .flat_map(|a| (b'a'..=b'z').map(move |b| [a, b]))
+            .filter(|label| hash_bytes(label) == 3)
+            .count();
+        assert_eq!(labels.len(), brute);
+        assert_eq!(labels_hashing_to(0, 0, "ab")?.collect::<Vec<_>>(), vec![""]);
+        assert_eq!(labels_hashing_to(0, 3, "")?.count(), 0);
+        let long: Vec<String> = labels_hashing_to(200, 6, "xyz")?.take(3).collect();
+        assert!(long
+            .iter()
+            .all(|label| label.len() == 6 && hash(label) == Ok(200)));
+        // 259 would otherwise land on the labels for 3
+        assert!(matches!(
+            labels_hashing_to(259, 2, "abc"),
+            Err(HashError::OutOfRange(259))
+        ));
+        assert!(matches!(
+            labels_hashing_to(3, 2, "abé"),
+            Err(HashError::NonAscii {
+                ch: 'é',
+                position: 2
+            })
+        ));
+        Ok(())
+    }
 }
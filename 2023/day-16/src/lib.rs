@@ -1,8 +1,28 @@
+use std::collections::HashMap;
+
+pub mod render;
+
 pub const NORTH: usize = 1;
 pub const EAST: usize = 2;
 pub const SOUTH: usize = 4;
 pub const WEST: usize = 8;
 
+// Directions a beam has travelled through each tile, indexed by row then column
+pub type Energized = Vec<Vec<usize>>;
+
+pub const DIRECTIONS: [usize; 4] = [NORTH, EAST, SOUTH, WEST];
+
+// One line per tile: the tile character followed by `<in>=<outs>` pairs, where
+// `<in>` is the direction the beam is travelling and `<outs>` the directions it
+// leaves in. Incoming directions that aren't listed absorb the beam.
+pub const STANDARD_OPTICS: &str = r"
+. N=N E=E S=S W=W
+/ N=E E=N S=W W=S
+\ N=W E=S S=E W=N
+| N=N S=S E=NS W=NS
+- E=E W=W N=EW S=EW
+";
+
 pub struct Grid {
     pub data: Vec<u8>,
     pub width: usize,
@@ -68,6 +88,64 @@ impl<'a> Iterator for GridIterator<'a> {
     }
 }
 
+fn dir_index(dir: usize) -> usize {
+    dir.trailing_zeros() as usize
+}
+
+fn parse_dirs(input: &str) -> Option<usize> {
+    input.chars().try_fold(0, |dirs, c| {
+        let dir = match c {
+            'N' => NORTH,
+            'E' => EAST,
+            'S' => SOUTH,
+            'W' => WEST,
+            _ => return None,
+        };
+        Some(dirs | dir)
+    })
+}
+
+pub struct Optics {
+    table: HashMap<char, [usize; 4]>,
+}
+
+impl Optics {
+    pub fn standard() -> Optics {
+        Optics::from_config(STANDARD_OPTICS).expect("the built-in optics table is valid")
+    }
+
+    pub fn from_config(config: &str) -> Result<Optics, String> {
+        let mut table = HashMap::new();
+        for line in config.lines().filter(|line| !line.trim().is_empty()) {
+            let mut fields = line.split_whitespace();
+            let mut tile_chars = fields.next().unwrap_or_default().chars();
+            let tile = match (tile_chars.next(), tile_chars.next()) {
+                (Some(tile), None) => tile,
+                _ => return Err(line.to_string()),
+            };
+            let mut outgoing = [0; 4];
+            for field in fields {
+                let (incoming, outs) = field.split_once('=').ok_or(line.to_string())?;
+                let incoming = parse_dirs(incoming).ok_or(line.to_string())?;
+                if incoming.count_ones() != 1 {
+                    return Err(line.to_string());
+                }
+                outgoing[dir_index(incoming)] = parse_dirs(outs).ok_or(line.to_string())?;
+            }
+            if table.insert(tile, outgoing).is_some() {
+                return Err(line.to_string());
+            }
+        }
+        Ok(Optics { table })
+    }
+
+    pub fn outgoing(&self, tile: char, dir: usize) -> Option<usize> {
+        self.table
+            .get(&tile)
+            .map(|outgoing| outgoing[dir_index(dir)])
+    }
+}
+
 #[derive(Debug)]
 pub struct GridPos {
     pub row: usize,
@@ -76,202 +154,26 @@ pub struct GridPos {
 }
 
 impl GridPos {
-    pub fn next(self, grid: &Grid) -> Vec<GridPos> {
-        let mut result: Vec<GridPos> = Vec::new();
-        match grid.get(self.col, self.row) {
-            '.' => match self.dir {
-                NORTH => {
-                    if self.row > 0 {
-                        result.push(GridPos {
-                            row: self.row - 1,
-                            col: self.col,
-                            dir: self.dir,
-                        })
-                    }
-                }
-                WEST => {
-                    if self.col > 0 {
-                        result.push(GridPos {
-                            row: self.row,
-                            col: self.col - 1,
-                            dir: self.dir,
-                        })
-                    }
-                }
-                SOUTH => {
-                    if self.row + 1 < grid.height {
-                        result.push(GridPos {
-                            row: self.row + 1,
-                            col: self.col,
-                            dir: self.dir,
-                        })
-                    }
-                }
-                EAST => {
-                    if self.col + 1 < grid.width {
-                        result.push(GridPos {
-                            row: self.row,
-                            col: self.col + 1,
-                            dir: self.dir,
-                        })
-                    }
-                }
-                _ => panic!(),
-            },
-            '/' => match self.dir {
-                NORTH => {
-                    if self.col + 1 < grid.width {
-                        result.push(GridPos {
-                            row: self.row,
-                            col: self.col + 1,
-                            dir: EAST,
-                        })
-                    }
-                }
-                WEST => {
-                    if self.row + 1 < grid.height {
-                        result.push(GridPos {
-                            row: self.row + 1,
-                            col: self.col,
-                            dir: SOUTH,
-                        })
-                    }
-                }
-                SOUTH => {
-                    if self.col > 0 {
-                        result.push(GridPos {
-                            row: self.row,
-                            col: self.col - 1,
-                            dir: WEST,
-                        })
-                    }
-                }
-                EAST => {
-                    if self.row > 0 {
-                        result.push(GridPos {
-                            row: self.row - 1,
-                            col: self.col,
-                            dir: NORTH,
-                        })
-                    }
-                }
-                _ => panic!(),
-            },
-            '\\' => match self.dir {
-                NORTH => {
-                    if self.col > 0 {
-                        result.push(GridPos {
-                            row: self.row,
-                            col: self.col - 1,
-                            dir: WEST,
-                        })
-                    }
-                }
-                WEST => {
-                    if self.row > 0 {
-                        result.push(GridPos {
-                            row: self.row - 1,
-                            col: self.col,
-                            dir: NORTH,
-                        })
-                    }
-                }
-                SOUTH => {
-                    if self.col + 1 < grid.width {
-                        result.push(GridPos {
-                            row: self.row,
-                            col: self.col + 1,
-                            dir: EAST,
-                        })
-                    }
-                }
-                EAST => {
-                    if self.row + 1 < grid.height {
-                        result.push(GridPos {
-                            row: self.row + 1,
-                            col: self.col,
-                            dir: SOUTH,
-                        })
-                    }
-                }
-                _ => panic!(),
-            },
-            '|' => match self.dir {
-                NORTH => {
-                    if self.row > 0 {
-                        result.push(GridPos {
-                            row: self.row - 1,
-                            col: self.col,
-                            dir: self.dir,
-                        })
-                    }
-                }
-                EAST | WEST => {
-                    if self.row > 0 {
-                        result.push(GridPos {
-                            row: self.row - 1,
-                            col: self.col,
-                            dir: NORTH,
-                        })
-                    };
-                    if self.row + 1 < grid.height {
-                        result.push(GridPos {
-                            row: self.row + 1,
-                            col: self.col,
-                            dir: SOUTH,
-                        })
-                    };
-                }
-                SOUTH => {
-                    if self.row + 1 < grid.height {
-                        result.push(GridPos {
-                            row: self.row + 1,
-                            col: self.col,
-                            dir: self.dir,
-                        })
-                    }
-                }
-                _ => panic!(),
-            },
-            '-' => match self.dir {
-                NORTH | SOUTH => {
-                    if self.col > 0 {
-                        result.push(GridPos {
-                            row: self.row,
-                            col: self.col - 1,
-                            dir: WEST,
-                        })
-                    };
-                    if self.col + 1 < grid.width {
-                        result.push(GridPos {
-                            row: self.row,
-                            col: self.col + 1,
-                            dir: EAST,
-                        })
-                    };
-                }
-                WEST => {
-                    if self.col > 0 {
-                        result.push(GridPos {
-                            row: self.row,
-                            col: self.col - 1,
-                            dir: self.dir,
-                        })
-                    }
-                }
-                EAST => {
-                    if self.col + 1 < grid.width {
-                        result.push(GridPos {
-                            row: self.row,
-                            col: self.col + 1,
-                            dir: self.dir,
-                        })
-                    }
-                }
-                _ => panic!(),
-            },
-            _ => panic!("Unknown block @ {}, {}", self.col, self.row),
-        }
-        result
+    fn step(&self, grid: &Grid, dir: usize) -> Option<GridPos> {
+        let (row, col) = match dir {
+            NORTH if self.row > 0 => (self.row - 1, self.col),
+            EAST if self.col + 1 < grid.width => (self.row, self.col + 1),
+            SOUTH if self.row + 1 < grid.height => (self.row + 1, self.col),
+            WEST if self.col > 0 => (self.row, self.col - 1),
+            _ => return None,
+        };
+        Some(GridPos { row, col, dir })
+    }
+
+    // Returns `None` if the tile under the beam isn't described by the optics table
+    pub fn next(self, grid: &Grid, optics: &Optics) -> Option<Vec<GridPos>> {
+        let outgoing = optics.outgoing(grid.get(self.col, self.row), self.dir)?;
+        Some(
+            DIRECTIONS
+                .iter()
+                .filter(|dir| outgoing & **dir != 0)
+                .filter_map(|dir| self.step(grid, *dir))
+                .collect(),
+        )
     }
 }
@@ -1,6 +1,10 @@
 use anyhow::Result;
-use day_16::{Grid, GridPos, EAST, NORTH, SOUTH, WEST};
+use day_16::render::{energized_map, play_ansi, write_gif};
+use day_16::{Energized, Grid, GridPos, Optics, EAST, NORTH, SOUTH, WEST};
 use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufWriter;
+use std::time::Duration;
 use thiserror::Error;
 extern crate pretty_env_logger;
 #[macro_use]
@@ -12,6 +16,8 @@ const AOC_DAY: &str = "16";
 pub enum AoCError {
     #[error("Unable to parse the input `{0}`")]
     ParsingError(String),
+    #[error("Unable to render the beam `{0}`")]
+    RenderError(String),
     #[error("An unknown error has occurred (super duper helpful error)")]
     Unknown,
 }
@@ -23,48 +29,142 @@ fn main() {
         "\n🎄🎄🎄🎄🎄 Advent of Code ||| Day {} 🎄🎄🎄🎄🎄\n",
         AOC_DAY
     );
-    match process_part_1(INPUT) {
+    // Usage: day-16 [--animate] [--gif <file>] [optics config]
+    let mut animate = false;
+    let mut gif_path = None;
+    let mut optics_path = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--animate" => animate = true,
+            "--gif" => gif_path = args.next(),
+            _ => optics_path = Some(arg),
+        }
+    }
+    let optics = match load_optics(optics_path) {
+        Ok(optics) => optics,
+        Err(e) => {
+            println!("Error: {}", e);
+            return;
+        }
+    };
+    if animate || gif_path.is_some() {
+        if let Err(e) = render_part_1(INPUT, &optics, animate, gif_path) {
+            println!("Error: {}", e);
+        }
+    }
+    match process_part_1(INPUT, &optics) {
         Ok(result) => println!("Part 1 result\n\t{}\n", result),
         Err(e) => println!("Error: {}", e),
     }
-    match process_part_2(INPUT) {
+    match process_part_2(INPUT, &optics) {
         Ok(result) => println!("Part 2 result\n\t{}", result),
         Err(e) => println!("Error: {}", e),
     }
 }
 
+fn load_optics(path: Option<String>) -> Result<Optics, AoCError> {
+    let Some(path) = path else {
+        return Ok(Optics::standard());
+    };
+    let config = std::fs::read_to_string(&path)
+        .map_err(|e| AoCError::ParsingError(format!("{path}: {e}")))?;
+    Optics::from_config(&config).map_err(AoCError::ParsingError)
+}
+
 fn is_visited(pos: &GridPos, visited: &[Vec<usize>]) -> bool {
     (visited[pos.row][pos.col] & pos.dir) == pos.dir
 }
 
-fn find_energized_tiles(grid: &Grid, initial_pos: GridPos) -> Result<usize, AoCError> {
-    let mut visited: Vec<Vec<usize>> = vec![vec![0; grid.width]; grid.height];
+// Runs the beam until it settles. If `frames` is given, the energised tiles are
+// captured after each step of the breadth-first search
+fn energize(
+    grid: &Grid,
+    optics: &Optics,
+    initial_pos: GridPos,
+    mut frames: Option<&mut Vec<Energized>>,
+) -> Result<Energized, AoCError> {
+    let mut visited: Energized = vec![vec![0; grid.width]; grid.height];
     let mut queue: VecDeque<GridPos> = VecDeque::new();
     visited[initial_pos.row][initial_pos.col] = initial_pos.dir;
     queue.push_back(initial_pos);
     while !queue.is_empty() {
-        let current_pos = queue.pop_front().unwrap();
-        trace!(
-            "At {}, {} ({})",
-            current_pos.row,
-            current_pos.col,
-            grid.get(current_pos.col, current_pos.row)
-        );
-        for next_pos in current_pos.next(grid) {
-            if !is_visited(&next_pos, &visited) {
-                visited[next_pos.row][next_pos.col] |= next_pos.dir;
-                queue.push_back(next_pos);
+        if let Some(frames) = frames.as_mut() {
+            frames.push(visited.clone());
+        }
+        for _ in 0..queue.len() {
+            let current_pos = queue.pop_front().unwrap();
+            trace!(
+                "At {}, {} ({})",
+                current_pos.row,
+                current_pos.col,
+                grid.get(current_pos.col, current_pos.row)
+            );
+            let (row, col) = (current_pos.row, current_pos.col);
+            let next_positions = current_pos.next(grid, optics).ok_or_else(|| {
+                AoCError::ParsingError(format!("Unknown block @ {}, {}", col, row))
+            })?;
+            for next_pos in next_positions {
+                if !is_visited(&next_pos, &visited) {
+                    visited[next_pos.row][next_pos.col] |= next_pos.dir;
+                    queue.push_back(next_pos);
+                }
             }
         }
     }
+    if let Some(frames) = frames {
+        frames.push(visited.clone());
+    }
+    Ok(visited)
+}
+
+fn find_energized_tiles(
+    grid: &Grid,
+    optics: &Optics,
+    initial_pos: GridPos,
+) -> Result<usize, AoCError> {
+    let visited = energize(grid, optics, initial_pos, None)?;
     let result = visited.iter().flatten().filter(|val| **val != 0).count();
     Ok(result)
 }
 
-fn process_part_1(input: &str) -> Result<usize, AoCError> {
+fn render_part_1(
+    input: &str,
+    optics: &Optics,
+    animate: bool,
+    gif_path: Option<String>,
+) -> Result<(), AoCError> {
+    let grid = Grid::new(input);
+    let mut frames = Vec::new();
+    let initial_pos = GridPos {
+        row: 0,
+        col: 0,
+        dir: EAST,
+    };
+    let energized = energize(&grid, optics, initial_pos, Some(&mut frames))?;
+    if animate {
+        play_ansi(
+            &mut std::io::stdout(),
+            &grid,
+            &frames,
+            Duration::from_millis(50),
+        )
+        .map_err(|e| AoCError::RenderError(e.to_string()))?;
+        println!("\n{}\n", energized_map(&energized));
+    }
+    if let Some(path) = gif_path {
+        let file = File::create(&path).map_err(|e| AoCError::RenderError(e.to_string()))?;
+        write_gif(BufWriter::new(file), &grid, &frames, 4, 5)
+            .map_err(|e| AoCError::RenderError(e.to_string()))?;
+    }
+    Ok(())
+}
+
+fn process_part_1(input: &str, optics: &Optics) -> Result<usize, AoCError> {
     let grid = Grid::new(input);
     let result = find_energized_tiles(
         &grid,
+        optics,
         GridPos {
             row: 0,
             col: 0,
@@ -74,7 +174,7 @@ fn process_part_1(input: &str) -> Result<usize, AoCError> {
     Ok(result)
 }
 
-fn process_part_2(input: &str) -> Result<usize, AoCError> {
+fn process_part_2(input: &str, optics: &Optics) -> Result<usize, AoCError> {
     let grid = Grid::new(input);
     let mut result = 0;
 
@@ -82,6 +182,7 @@ fn process_part_2(input: &str) -> Result<usize, AoCError> {
     for pos in 0..grid.width {
         let top_result = find_energized_tiles(
             &grid,
+            optics,
             GridPos {
                 row: 0,
                 col: pos,
@@ -90,6 +191,7 @@ fn process_part_2(input: &str) -> Result<usize, AoCError> {
         )?;
         let bot_result = find_energized_tiles(
             &grid,
+            optics,
             GridPos {
                 row: grid.height - 1,
                 col: pos,
@@ -103,6 +205,7 @@ fn process_part_2(input: &str) -> Result<usize, AoCError> {
     for pos in 0..grid.height {
         let left_result = find_energized_tiles(
             &grid,
+            optics,
             GridPos {
                 row: pos,
                 col: 0,
@@ -111,6 +214,7 @@ fn process_part_2(input: &str) -> Result<usize, AoCError> {
         )?;
         let right_result = find_energized_tiles(
             &grid,
+            optics,
             GridPos {
                 row: pos,
                 col: grid.width - 1,
@@ -138,7 +242,7 @@ mod tests {
 .-.-/..|..
 .|....-|.\
 ..//.|....";
-        assert_eq!(process_part_1(input_1)?, 46);
+        assert_eq!(process_part_1(input_1, &Optics::standard())?, 46);
         Ok(())
     }
     #[test]
@@ -153,7 +257,66 @@ mod tests {
 .-.-/..|..
 .|....-|.\
 ..//.|....";
-        assert_eq!(process_part_2(input_1)?, 51);
+        assert_eq!(process_part_2(input_1, &Optics::standard())?, 51);
+        Ok(())
+    }
+    #[test]
+    fn custom_optics() -> Result<()> {
+        // `#` absorbs every beam, `x` splits every beam perpendicular to its direction
+        let optics = Optics::from_config(&format!(
+            "{}\n# \nx N=EW E=NS S=EW W=NS",
+            day_16::STANDARD_OPTICS
+        ))
+        .map_err(AoCError::ParsingError)?;
+        let input_1 = r"..#.
+.x..
+....";
+        assert_eq!(process_part_1(input_1, &optics)?, 3);
+        let input_2 = r".x..
+....
+....";
+        assert_eq!(process_part_1(input_2, &optics)?, 4);
+        assert!(Optics::from_config("| N=Q").is_err());
+        assert!(process_part_1("..?.", &Optics::standard()).is_err());
+        Ok(())
+    }
+    #[test]
+    fn energized_map_matches_example() -> Result<()> {
+        let input_1 = r".|...\....
+|.-.\.....
+.....|-...
+........|.
+..........
+.........\
+..../.\\..
+.-.-/..|..
+.|....-|.\
+..//.|....";
+        let expected = "######....
+.#...#....
+.#...#####
+.#...##...
+.#...##...
+.#...##...
+.#..####..
+########..
+.#######..
+.#...#.#..";
+        let grid = Grid::new(input_1);
+        let start = GridPos {
+            row: 0,
+            col: 0,
+            dir: EAST,
+        };
+        let mut frames = Vec::new();
+        let energized = energize(&grid, &Optics::standard(), start, Some(&mut frames))?;
+        assert_eq!(energized_map(&energized), expected);
+        assert_eq!(energized_map(&frames[0]).matches('#').count(), 1);
+        assert_eq!(frames.last(), Some(&energized));
+        let mut gif = Vec::new();
+        write_gif(&mut gif, &grid, &frames, 2, 5)?;
+        assert!(gif.starts_with(b"GIF89a"));
+        assert!(write_gif(&mut Vec::new(), &grid, &frames, 10_000, 5).is_err());
         Ok(())
     }
 }
@@ -0,0 +1,172 @@
+use crate::{Energized, Grid, EAST, NORTH, SOUTH, WEST};
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+const ANSI_RESET: &str = "\x1b[0m";
+const ANSI_BEAM: &str = "\x1b[1;33m";
+const ANSI_OPTIC: &str = "\x1b[1;37m";
+const ANSI_EMPTY: &str = "\x1b[2;37m";
+const ANSI_CLEAR: &str = "\x1b[2J";
+const ANSI_HOME: &str = "\x1b[H";
+
+// Background, energised floor, beam, unlit optic, lit optic
+const GIF_PALETTE: [u8; 15] = [
+    0x0f, 0x0f, 0x23, //
+    0x3a, 0x2f, 0x0b, //
+    0xff, 0xcc, 0x00, //
+    0x90, 0x90, 0xa0, //
+    0xff, 0xff, 0xff, //
+];
+const GIF_BACKGROUND: u8 = 0;
+const GIF_ENERGIZED: u8 = 1;
+const GIF_BEAM: u8 = 2;
+const GIF_OPTIC: u8 = 3;
+const GIF_LIT_OPTIC: u8 = 4;
+
+// The final map in the puzzle's notation, `#` for energised tiles and `.` otherwise
+pub fn energized_map(energized: &Energized) -> String {
+    energized
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|dirs| if *dirs != 0 { '#' } else { '.' })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Empty tiles show the beam the way the puzzle does: an arrow for a single beam,
+// or the number of beams passing through
+fn beam_glyph(tile: char, dirs: usize) -> char {
+    if tile != '.' || dirs == 0 {
+        return tile;
+    }
+    match dirs {
+        NORTH => '^',
+        EAST => '>',
+        SOUTH => 'v',
+        WEST => '<',
+        _ => char::from_digit(dirs.count_ones(), 10).unwrap_or('*'),
+    }
+}
+
+pub fn ansi_frame(grid: &Grid, energized: &Energized) -> String {
+    let mut result = String::new();
+    for (row, dirs_row) in energized.iter().enumerate() {
+        for (col, dirs) in dirs_row.iter().enumerate() {
+            let tile = grid.get(col, row);
+            let colour = match (tile, *dirs) {
+                (_, dirs) if dirs != 0 => ANSI_BEAM,
+                ('.', _) => ANSI_EMPTY,
+                _ => ANSI_OPTIC,
+            };
+            result.push_str(colour);
+            result.push(beam_glyph(tile, *dirs));
+        }
+        result.push_str(ANSI_RESET);
+        result.push('\n');
+    }
+    result
+}
+
+pub fn play_ansi(
+    out: &mut impl Write,
+    grid: &Grid,
+    frames: &[Energized],
+    delay: Duration,
+) -> std::io::Result<()> {
+    write!(out, "{}", ANSI_CLEAR)?;
+    for frame in frames {
+        write!(out, "{}{}", ANSI_HOME, ansi_frame(grid, frame))?;
+        out.flush()?;
+        thread::sleep(delay);
+    }
+    Ok(())
+}
+
+fn draw_tile(
+    pixels: &mut [u8],
+    width: usize,
+    (row, col): (usize, usize),
+    scale: usize,
+    tile: char,
+    dirs: usize,
+) {
+    let (left, top) = (col * scale, row * scale);
+    let mid = scale / 2;
+    let optic = if dirs != 0 { GIF_LIT_OPTIC } else { GIF_OPTIC };
+    for dy in 0..scale {
+        for dx in 0..scale {
+            let on_optic = match tile {
+                '.' => false,
+                '/' => dx + dy == scale - 1,
+                '\\' => dx == dy,
+                '|' => dx == mid,
+                '-' => dy == mid,
+                _ => dx > 0 && dy > 0 && dx < scale - 1 && dy < scale - 1,
+            };
+            let on_beam = (dirs & (EAST | WEST) != 0 && dy == mid)
+                || (dirs & (NORTH | SOUTH) != 0 && dx == mid);
+            let colour = if on_optic {
+                optic
+            } else if on_beam {
+                GIF_BEAM
+            } else if dirs != 0 {
+                GIF_ENERGIZED
+            } else {
+                GIF_BACKGROUND
+            };
+            pixels[(top + dy) * width + left + dx] = colour;
+        }
+    }
+}
+
+pub fn gif_pixels(grid: &Grid, energized: &Energized, scale: usize) -> Vec<u8> {
+    let width = grid.width * scale;
+    let mut pixels = vec![GIF_BACKGROUND; width * grid.height * scale];
+    for (tile, row, col) in grid {
+        draw_tile(
+            &mut pixels,
+            width,
+            (row, col),
+            scale,
+            tile,
+            energized[row][col],
+        );
+    }
+    pixels
+}
+
+// `delay` is in hundredths of a second, as GIF stores it
+pub fn write_gif(
+    out: impl Write,
+    grid: &Grid,
+    frames: &[Energized],
+    scale: usize,
+    delay: u16,
+) -> Result<(), gif::EncodingError> {
+    // GIF sizes are 16 bits, so a picture that won't fit is refused rather than truncated
+    let side = |tiles: usize| {
+        tiles
+            .checked_mul(scale)
+            .and_then(|pixels| u16::try_from(pixels).ok())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} tiles at scale {} don't fit in a GIF", tiles, scale),
+                )
+            })
+    };
+    let (width, height) = (side(grid.width)?, side(grid.height)?);
+    let mut encoder = gif::Encoder::new(out, width, height, &GIF_PALETTE)?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+    for energized in frames {
+        let pixels = gif_pixels(grid, energized, scale);
+        let mut frame = gif::Frame::from_indexed_pixels(width, height, &pixels, None);
+        frame.delay = delay;
+        encoder.write_frame(&frame)?;
+    }
+    Ok(())
+}
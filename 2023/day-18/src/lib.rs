@@ -1,3 +1,5 @@
+pub mod render;
+
 pub const MOVES: [[i64; 2]; 4] = [
     // N - E - S - W
     [0, -1],
@@ -11,16 +13,78 @@ pub const EAST: usize = 1;
 pub const SOUTH: usize = 2;
 pub const WEST: usize = 3;
 
-fn get_direction(dir: char) -> usize {
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandFormat {
+    // `R 6 (#70c710)`, direction and steps with the colour alongside
+    Plan,
+    // Part 2, the steps and direction are hidden in the colour code
+    Hex,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ParseErrorKind {
+    #[error("unknown direction `{0}`")]
+    Direction(String),
+    #[error("malformed step count `{0}`")]
+    Steps(String),
+    #[error("malformed colour code `{0}`")]
+    Color(String),
+    #[error("expected 3 fields, found {0}")]
+    FieldCount(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("line {line}, column {column}: {kind}")]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+fn get_direction(dir: &str) -> Option<usize> {
     match dir {
-        'U' => NORTH,
-        'R' => EAST,
-        'L' => WEST,
-        'D' => SOUTH,
-        _ => panic!(),
+        "U" => Some(NORTH),
+        "R" => Some(EAST),
+        "L" => Some(WEST),
+        "D" => Some(SOUTH),
+        _ => None,
+    }
+}
+
+fn get_hex_direction(digit: char) -> Option<usize> {
+    match digit {
+        '0' => Some(EAST),
+        '1' => Some(SOUTH),
+        '2' => Some(WEST),
+        '3' => Some(NORTH),
+        _ => None,
     }
 }
 
+// Splits on spaces, keeping the 1-based column each field starts at
+fn fields(input: &str) -> Vec<(usize, &str)> {
+    input
+        .split(' ')
+        .scan(1, |column, field| {
+            let start = *column;
+            *column += field.len() + 1;
+            Some((start, field))
+        })
+        .filter(|(_, field)| !field.is_empty())
+        .collect()
+}
+
+// The colour is `(#rrggbb)`, returns the six hex digits
+fn color_digits(field: &str) -> Option<&str> {
+    let digits = field.strip_prefix("(#")?.strip_suffix(')')?;
+    if digits.len() != 6 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(digits)
+}
+
 #[derive(Debug)]
 pub struct Command {
     pub direction: usize,
@@ -29,47 +93,61 @@ pub struct Command {
 }
 
 impl Command {
-    pub fn new(input: &str) -> Option<Self> {
-        let mut items = input.split(' ');
-        let direction = get_direction(items.next()?.chars().next()?);
-        let steps = match items.next()?.parse::<usize>() {
-            Ok(x) => x,
-            _ => return None,
-        };
-        let color = match u32::from_str_radix(
-            items.next()?.trim_start_matches("(#").trim_end_matches(')'),
-            16,
-        ) {
-            Ok(x) => x,
-            _ => return None,
-        };
-        Some(Command {
-            direction,
-            steps,
-            color,
-        })
-    }
-    pub fn new_part2(input: &str) -> Option<Self> {
-        let items = input.split(' ');
-        let hex_string = items.last()?.trim_start_matches("(#").trim_end_matches(')');
-        let steps = match usize::from_str_radix(&hex_string[..5], 16) {
-            Ok(x) => x,
-            _ => return None,
-        };
-        let direction = match hex_string.chars().last() {
-            Some(x) => match x {
-                '0' => WEST,
-                '1' => SOUTH,
-                '2' => EAST,
-                '3' => NORTH,
-                _ => return None,
-            },
-            _ => return None,
+    // `line` is the 1-based line number, only used for error reporting
+    pub fn parse(input: &str, line: usize, format: CommandFormat) -> Result<Self, ParseError> {
+        let error = |column, kind| ParseError { line, column, kind };
+        let fields = fields(input);
+        let [(dir_col, dir), (steps_col, steps), (color_col, color)] = fields[..] else {
+            return Err(error(1, ParseErrorKind::FieldCount(fields.len())));
         };
-        Some(Command {
-            direction,
-            steps,
-            color: 0,
-        })
+        let digits = color_digits(color)
+            .ok_or_else(|| error(color_col, ParseErrorKind::Color(color.to_string())))?;
+        let color_value = u32::from_str_radix(digits, 16)
+            .map_err(|_| error(color_col, ParseErrorKind::Color(color.to_string())))?;
+        match format {
+            CommandFormat::Plan => {
+                let direction = get_direction(dir)
+                    .ok_or_else(|| error(dir_col, ParseErrorKind::Direction(dir.to_string())))?;
+                let steps = steps
+                    .parse::<usize>()
+                    .map_err(|_| error(steps_col, ParseErrorKind::Steps(steps.to_string())))?;
+                Ok(Command {
+                    direction,
+                    steps,
+                    color: color_value,
+                })
+            }
+            CommandFormat::Hex => {
+                // Columns point past the `(#` at the digits themselves
+                let direction_digit = &digits[5..];
+                let direction = direction_digit
+                    .chars()
+                    .next()
+                    .and_then(get_hex_direction)
+                    .ok_or_else(|| {
+                        error(
+                            color_col + 7,
+                            ParseErrorKind::Direction(direction_digit.to_string()),
+                        )
+                    })?;
+                let steps = usize::from_str_radix(&digits[..5], 16)
+                    .map_err(|_| error(color_col + 2, ParseErrorKind::Steps(digits.to_string())))?;
+                Ok(Command {
+                    direction,
+                    steps,
+                    color: color_value,
+                })
+            }
+        }
     }
 }
+
+// Blank lines are skipped, but still counted for the line numbers in errors
+pub fn parse_commands(input: &str, format: CommandFormat) -> Result<Vec<Command>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| Command::parse(line, index + 1, format))
+        .collect()
+}
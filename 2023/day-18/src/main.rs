@@ -1,5 +1,9 @@
 use anyhow::Result;
-use day_18::{Command, MOVES};
+use common::geometry::Polygon;
+use day_18::render::rasterize;
+use day_18::{parse_commands, Command, CommandFormat, ParseError, MOVES};
+use std::fs::File;
+use std::io::BufWriter;
 use thiserror::Error;
 extern crate pretty_env_logger;
 #[macro_use]
@@ -11,6 +15,10 @@ const AOC_DAY: &str = "18";
 pub enum AoCError {
     #[error("Unable to parse the input `{0}`")]
     ParsingError(String),
+    #[error("Unable to parse the dig plan, {0}")]
+    CommandError(#[from] ParseError),
+    #[error("Unable to render the lagoon `{0}`")]
+    RenderError(String),
     #[error("An unknown error has occurred (super duper helpful error)")]
     Unknown,
 }
@@ -22,6 +30,14 @@ fn main() {
         "\n🎄🎄🎄🎄🎄 Advent of Code ||| Day {} 🎄🎄🎄🎄🎄\n",
         AOC_DAY
     );
+    // Usage: day-18 [--part2] [--scale <pixels>] [--ppm <file>] [--png <file>]
+    let options = match parse_options(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            println!("Error: {}", e);
+            return;
+        }
+    };
     match process_part_1(INPUT) {
         Ok(result) => println!("Part 1 result\n\t{}\n", result),
         Err(e) => println!("Error: {}", e),
@@ -30,50 +46,93 @@ fn main() {
         Ok(result) => println!("Part 2 result\n\t{}", result),
         Err(e) => println!("Error: {}", e),
     }
+    if options.ppm_path.is_some() || options.png_path.is_some() {
+        if let Err(e) = render(INPUT, &options) {
+            println!("Error: {}", e);
+        }
+    }
+}
+
+struct RenderOptions {
+    part_2: bool,
+    scale: usize,
+    ppm_path: Option<String>,
+    png_path: Option<String>,
 }
 
-fn create_command(input: &str) -> Result<Command, AoCError> {
-    match Command::new(input) {
-        Some(x) => Ok(x),
-        _ => Err(AoCError::ParsingError(input.to_string())),
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<RenderOptions, AoCError> {
+    let mut options = RenderOptions {
+        part_2: false,
+        scale: 4,
+        ppm_path: None,
+        png_path: None,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--part2" => options.part_2 = true,
+            "--scale" => {
+                options.scale = args
+                    .next()
+                    .and_then(|x| x.parse().ok())
+                    .filter(|scale| *scale > 0)
+                    .ok_or_else(|| {
+                        AoCError::ParsingError(
+                            "`--scale` needs a positive number of pixels".to_string(),
+                        )
+                    })?
+            }
+            "--ppm" => options.ppm_path = args.next(),
+            "--png" => options.png_path = args.next(),
+            _ => println!("Ignoring unknown argument `{}`", arg),
+        }
     }
+    Ok(options)
 }
 
-fn polygon_area(commands: &[Command]) -> i64 {
-    let mut area = 0;
-    let mut path_length = 0;
-    let mut row = 0;
-    let mut col = 0;
-    for command in commands.iter() {
-        let next_col = col + MOVES[command.direction][0] * command.steps as i64;
-        let next_row = row + MOVES[command.direction][1] * command.steps as i64;
-        path_length += command.steps as i64;
-        area += (row - next_row) * (col + next_col);
-        row = next_row;
-        col = next_col;
+// Part 2's trench is millions of tiles across, so it's drawn with compressed coordinates
+fn render(input: &str, options: &RenderOptions) -> Result<(), AoCError> {
+    let format = if options.part_2 {
+        CommandFormat::Hex
+    } else {
+        CommandFormat::Plan
+    };
+    let commands = parse_commands(input, format)?;
+    let raster = rasterize(&commands, options.scale, options.part_2);
+    let render_error = |e: &dyn std::fmt::Display| AoCError::RenderError(e.to_string());
+    if let Some(path) = &options.ppm_path {
+        let file = File::create(path).map_err(|e| render_error(&e))?;
+        raster
+            .write_ppm(&mut BufWriter::new(file))
+            .map_err(|e| render_error(&e))?;
     }
+    if let Some(path) = &options.png_path {
+        let file = File::create(path).map_err(|e| render_error(&e))?;
+        raster
+            .write_png(BufWriter::new(file))
+            .map_err(|e| render_error(&e))?;
+    }
+    Ok(())
+}
 
-    (area / 2).abs() + path_length / 2 + 1
+fn polygon_area(commands: &[Command]) -> i128 {
+    let trench = Polygon::from_moves(
+        (0, 0),
+        commands
+            .iter()
+            .map(|command| (MOVES[command.direction], command.steps as i64)),
+    );
+    trench.lattice_points()
 }
 
-fn process_part_1(input: &str) -> Result<i64, AoCError> {
-    let command_result: Result<Vec<Command>, _> = input.lines().map(create_command).collect();
-    let commands = command_result.unwrap();
+fn process_part_1(input: &str) -> Result<i128, AoCError> {
+    let commands = parse_commands(input, CommandFormat::Plan)?;
     trace!("{:?}", commands);
     let area = polygon_area(&commands);
     Ok(area)
 }
 
-fn create_command_2(input: &str) -> Result<Command, AoCError> {
-    match Command::new_part2(input) {
-        Some(x) => Ok(x),
-        _ => Err(AoCError::ParsingError(input.to_string())),
-    }
-}
-
-fn process_part_2(input: &str) -> Result<i64, AoCError> {
-    let command_result: Result<Vec<Command>, _> = input.lines().map(create_command_2).collect();
-    let commands = command_result.unwrap();
+fn process_part_2(input: &str) -> Result<i128, AoCError> {
+    let commands = parse_commands(input, CommandFormat::Hex)?;
     let area = polygon_area(&commands);
     Ok(area)
 }
@@ -121,4 +180,96 @@ U 2 (#7a21e3)";
         assert_eq!(process_part_2(input_1)?, 952408144115);
         Ok(())
     }
+    #[test]
+    fn render_lagoon() -> Result<()> {
+        let input_1 = "R 6 (#70c710)
+D 5 (#0dc571)
+L 2 (#5713f0)
+D 2 (#d2c081)
+R 2 (#59c680)
+D 2 (#411b91)
+L 5 (#8ceee2)
+U 2 (#caa173)
+L 1 (#1b58a2)
+U 2 (#caa171)
+R 2 (#7807d2)
+U 3 (#a77fa3)
+L 2 (#015232)
+U 2 (#7a21e3)";
+        let commands = parse_commands(input_1, CommandFormat::Plan)?;
+        let raster = rasterize(&commands, 1, false);
+        assert_eq!((raster.width, raster.height), (7, 10));
+        // The top edge is drawn in its colour, corners take the colour of the later edge
+        assert!(raster.pixels[1..6]
+            .iter()
+            .all(|rgb| *rgb == [0x70, 0xc7, 0x10]));
+        let background = raster.pixels[7 * 3];
+        let dug = raster
+            .pixels
+            .iter()
+            .filter(|rgb| **rgb != background)
+            .count();
+        assert_eq!(dug, 62);
+
+        let scaled = rasterize(&commands, 3, false);
+        assert_eq!((scaled.width, scaled.height), (21, 30));
+        let mut ppm = Vec::new();
+        scaled.write_ppm(&mut ppm)?;
+        assert!(ppm.starts_with(b"P6\n21 30\n255\n"));
+        assert_eq!(ppm.len(), 13 + 21 * 30 * 3);
+
+        let commands_2 = parse_commands(input_1, CommandFormat::Hex)?;
+        let compressed = rasterize(&commands_2, 1, true);
+        assert!(compressed.width < 20 && compressed.height < 20);
+        let mut png = Vec::new();
+        compressed.write_png(&mut png)?;
+        assert!(png.starts_with(b"\x89PNG"));
+        Ok(())
+    }
+    #[test]
+    fn render_options() -> Result<()> {
+        let parse = |args: &[&str]| parse_options(args.iter().map(|arg| arg.to_string()));
+        let options = parse(&["--scale", "2", "--png", "lagoon.png"])?;
+        assert_eq!(options.scale, 2);
+        assert_eq!(options.png_path.as_deref(), Some("lagoon.png"));
+        assert_eq!(parse(&["--part2"])?.scale, 4);
+        assert!(parse(&["--scale", "big"]).is_err());
+        assert!(parse(&["--scale", "0"]).is_err());
+        assert!(parse(&["--scale"]).is_err());
+        Ok(())
+    }
+    #[test]
+    fn parse_errors() {
+        use day_18::ParseErrorKind;
+        let error = |input, format| parse_commands(input, format).unwrap_err();
+        assert_eq!(
+            error("R 6 (#70c710)\nX 5 (#0dc571)", CommandFormat::Plan),
+            ParseError {
+                line: 2,
+                column: 1,
+                kind: ParseErrorKind::Direction("X".to_string())
+            }
+        );
+        assert_eq!(
+            error("R six (#70c710)", CommandFormat::Plan).kind,
+            ParseErrorKind::Steps("six".to_string())
+        );
+        let bad_color = error("R 6 (#70c7)", CommandFormat::Hex);
+        assert_eq!((bad_color.line, bad_color.column), (1, 5));
+        assert_eq!(bad_color.kind, ParseErrorKind::Color("(#70c7)".to_string()));
+        let bad_hex_direction = error("R 6 (#70c714)", CommandFormat::Hex);
+        assert_eq!(bad_hex_direction.column, 12);
+        assert_eq!(
+            bad_hex_direction.kind,
+            ParseErrorKind::Direction("4".to_string())
+        );
+        assert_eq!(
+            error("R 6", CommandFormat::Plan).kind,
+            ParseErrorKind::FieldCount(2)
+        );
+        // The binary reports these instead of panicking
+        assert!(process_part_1("U 2 (#zzzzzz)").is_err());
+        assert!(process_part_2("R 2 3 (#70c710)").is_err());
+        assert!(parse_commands("R 6 (#70c710)\n\nD 5 (#0dc571)\n", CommandFormat::Plan).is_ok());
+    }
 }
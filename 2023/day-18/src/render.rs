@@ -0,0 +1,155 @@
+use crate::{Command, MOVES};
+use std::collections::VecDeque;
+use std::io::Write;
+
+const BACKGROUND: [u8; 3] = [0x10, 0x10, 0x18];
+const INTERIOR: [u8; 3] = [0x5a, 0x4a, 0x3a];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Unknown,
+    Outside,
+    Trench(u32),
+}
+
+// Splits one axis into cells, each covering the coordinates [start, next start).
+// Uncompressed, every coordinate gets its own cell. Compressed, only the coordinates
+// where the trench turns do, and each run of coordinates between them shares a cell.
+struct Axis {
+    starts: Vec<i64>,
+}
+
+impl Axis {
+    fn new(mut coords: Vec<i64>, compress: bool) -> Axis {
+        coords.sort_unstable();
+        coords.dedup();
+        let (min, max) = (coords[0], coords[coords.len() - 1]);
+        let starts = if compress {
+            let mut starts: Vec<i64> = coords
+                .iter()
+                .flat_map(|coord| [*coord, coord + 1])
+                .filter(|start| *start <= max)
+                .collect();
+            starts.dedup();
+            starts
+        } else {
+            (min..=max).collect()
+        };
+        Axis { starts }
+    }
+
+    fn len(&self) -> usize {
+        self.starts.len()
+    }
+
+    fn index(&self, coord: i64) -> usize {
+        self.starts.partition_point(|start| *start <= coord) - 1
+    }
+}
+
+pub struct Raster {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u8; 3]>,
+}
+
+fn corners(commands: &[Command]) -> Vec<(i64, i64, u32)> {
+    let mut col = 0;
+    let mut row = 0;
+    let mut result = vec![(0, 0, 0)];
+    for command in commands.iter() {
+        col += MOVES[command.direction][0] * command.steps as i64;
+        row += MOVES[command.direction][1] * command.steps as i64;
+        result.push((col, row, command.color));
+    }
+    result
+}
+
+// Cells are padded by one on every side so the outside is connected all the way round
+fn trace_trench(commands: &[Command], cols: &Axis, rows: &Axis) -> Vec<Vec<Cell>> {
+    let mut cells = vec![vec![Cell::Unknown; cols.len() + 2]; rows.len() + 2];
+    for pair in corners(commands).windows(2) {
+        let (start_col, start_row, _) = pair[0];
+        let (end_col, end_row, color) = pair[1];
+        let (col_a, col_b) = (cols.index(start_col), cols.index(end_col));
+        let (row_a, row_b) = (rows.index(start_row), rows.index(end_row));
+        for row in row_a.min(row_b)..=row_a.max(row_b) {
+            for col in col_a.min(col_b)..=col_a.max(col_b) {
+                cells[row + 1][col + 1] = Cell::Trench(color);
+            }
+        }
+    }
+    cells
+}
+
+fn fill_outside(cells: &mut [Vec<Cell>]) {
+    let (height, width) = (cells.len(), cells[0].len());
+    let mut queue: VecDeque<(usize, usize)> = VecDeque::from([(0, 0)]);
+    cells[0][0] = Cell::Outside;
+    while let Some((row, col)) = queue.pop_front() {
+        let neighbours = [
+            (row.wrapping_sub(1), col),
+            (row + 1, col),
+            (row, col.wrapping_sub(1)),
+            (row, col + 1),
+        ];
+        for (next_row, next_col) in neighbours {
+            if next_row < height && next_col < width && cells[next_row][next_col] == Cell::Unknown {
+                cells[next_row][next_col] = Cell::Outside;
+                queue.push_back((next_row, next_col));
+            }
+        }
+    }
+}
+
+fn color_to_rgb(color: u32) -> [u8; 3] {
+    [(color >> 16) as u8, (color >> 8) as u8, color as u8]
+}
+
+// Draws the trench with every edge in its colour and the lagoon filled in, `scale` pixels per cell
+pub fn rasterize(commands: &[Command], scale: usize, compress: bool) -> Raster {
+    let corners = corners(commands);
+    let cols = Axis::new(corners.iter().map(|corner| corner.0).collect(), compress);
+    let rows = Axis::new(corners.iter().map(|corner| corner.1).collect(), compress);
+    let mut cells = trace_trench(commands, &cols, &rows);
+    fill_outside(&mut cells);
+
+    let (width, height) = (cols.len() * scale, rows.len() * scale);
+    let mut pixels = Vec::with_capacity(width * height);
+    for row in cells[1..=rows.len()].iter() {
+        let line: Vec<[u8; 3]> = row[1..=cols.len()]
+            .iter()
+            .flat_map(|cell| {
+                let rgb = match cell {
+                    Cell::Outside => BACKGROUND,
+                    Cell::Unknown => INTERIOR,
+                    Cell::Trench(color) => color_to_rgb(*color),
+                };
+                std::iter::repeat_n(rgb, scale)
+            })
+            .collect();
+        for _ in 0..scale {
+            pixels.extend_from_slice(&line);
+        }
+    }
+    Raster {
+        width,
+        height,
+        pixels,
+    }
+}
+
+impl Raster {
+    pub fn write_ppm(&self, out: &mut impl Write) -> std::io::Result<()> {
+        write!(out, "P6\n{} {}\n255\n", self.width, self.height)?;
+        out.write_all(&self.pixels.concat())
+    }
+
+    pub fn write_png(&self, out: impl Write) -> Result<(), png::EncodingError> {
+        let mut encoder = png::Encoder::new(out, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels.concat())
+    }
+}
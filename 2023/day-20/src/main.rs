@@ -1,4 +1,5 @@
 use anyhow::Result;
+use common::math::{lcm_all, MathError};
 use day_20::{Logic, LogicType, Pulse};
 use log::{log_enabled, Level};
 use queue::Queue;
@@ -14,6 +15,8 @@ const AOC_DAY: &str = "20";
 pub enum AoCError {
     #[error("Unable to parse the input `{0}`")]
     ParsingError(String),
+    #[error("Unable to combine the cycles, {0}")]
+    MathError(#[from] MathError),
     #[error("An unknown error has occurred (super duper helpful error)")]
     Unknown,
 }
@@ -205,16 +208,14 @@ fn process_part_1(input: &str) -> Result<i64, AoCError> {
     };
     let mut watch_list = get_watch_list(&[], &token_map, inventory.len());
     let sim_result = run_sim(&mut inventory, &mut watch_list, start, 1000);
-    let result = if sim_result.1 > 0 {
-        let count = 1000 / sim_result.1;
-        sim_result.0 * count as i64 * count as i64
-    } else {
-        sim_result.0
+    let result = match 1000usize.checked_div(sim_result.1) {
+        Some(count) => sim_result.0 * count as i64 * count as i64,
+        None => sim_result.0,
     };
     Ok(result)
 }
 
-fn get_parts(inv: &Vec<Logic>, start: usize) -> Vec<Vec<Logic>> {
+fn get_parts(inv: &[Logic], start: usize) -> Vec<Vec<Logic>> {
     let mut sub_trees: Vec<Vec<Logic>> = Vec::new();
     for dest in inv[start].dest.iter() {
         let mut visited = vec![false; inv.len()];
@@ -248,13 +249,6 @@ fn get_parts(inv: &Vec<Logic>, start: usize) -> Vec<Vec<Logic>> {
     sub_trees
 }
 
-fn vec_lcm(nums: &[usize]) -> usize {
-    if nums.len() == 1 {
-        return nums[0];
-    }
-    num::integer::lcm::<usize>(nums[0], vec_lcm(&nums[1..]))
-}
-
 fn process_part_2(input: &str) -> Result<usize, AoCError> {
     let (inventory, token_map) = get_inventory(input)?;
     let start = match token_map.get("broadcaster") {
@@ -271,7 +265,7 @@ fn process_part_2(input: &str) -> Result<usize, AoCError> {
         let _ = run_sim(part, &mut watch_list, start, 10000);
         cycles.push(watch_list[*watch_id].2);
     }
-    Ok(vec_lcm(&cycles))
+    Ok(lcm_all(cycles)?)
 }
 
 #[cfg(test)]